@@ -0,0 +1,194 @@
+use std::path::{Path, PathBuf};
+
+use clc_jpd_lib::{
+    check_game, export_game_data, import_game_data, load_config_file, load_game_file, save_game_file,
+    scale_weapons, simulate_weapon, validate_game, GameDataExport, IssueSeverity,
+};
+
+const USAGE: &str = "Usage:
+  jpd-cli validate <data.json> [--repair]
+  jpd-cli export <data.json> <out.json> [--weapons-only | --loadouts-only]
+  jpd-cli import <data.json> <in.json>
+  jpd-cli scale <data.json> [--dx <factor>] [--dy <factor>] [--weapon <id>]...
+  jpd-cli simulate <data.json> <weapon_id> [--shots <count>] [--config <config.json>]";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("validate") => validate(&args[1..]),
+        Some("export") => export(&args[1..]),
+        Some("import") => import(&args[1..]),
+        Some("scale") => scale(&args[1..]),
+        Some("simulate") => simulate(&args[1..]),
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Positional arguments, then `--flag [value]` options in the order given
+type ParsedArgs<'a> = (Vec<&'a str>, Vec<(&'a str, Option<&'a str>)>);
+
+fn parse_args<'a>(
+    args: &'a [String],
+    value_flags: &[&str],
+    bool_flags: &[&str],
+) -> Result<ParsedArgs<'a>, String> {
+    let mut positionals = Vec::new();
+    let mut options = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if value_flags.contains(&arg.as_str()) {
+            let value = args.next().ok_or(format!("Missing value for `{}`", arg))?;
+            options.push((arg.as_str(), Some(value.as_str())));
+        } else if bool_flags.contains(&arg.as_str()) {
+            options.push((arg.as_str(), None));
+        } else if arg.starts_with("--") {
+            return Err(format!("Unknown option `{}`\n{}", arg, USAGE));
+        } else {
+            positionals.push(arg.as_str());
+        }
+    }
+
+    Ok((positionals, options))
+}
+fn option<'a>(options: &[(&str, Option<&'a str>)], flag: &str) -> Option<&'a str> {
+    options.iter().rev().find(|(name, _)| *name == flag).and_then(|(_, value)| *value)
+}
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid value `{}` for `{}`", value, flag))
+}
+
+fn validate(args: &[String]) -> Result<(), String> {
+    let (positionals, options) = parse_args(args, &[], &["--repair"])?;
+    let [data_path] = positionals[..] else { return Err(USAGE.to_string()); };
+    let repair = options.iter().any(|(name, _)| *name == "--repair");
+
+    let mut game = load_game_file(Path::new(data_path))?;
+    let report = check_game(&mut game, repair);
+    for repair in &report.repairs {
+        println!("{}", repair);
+    }
+    for issue in &report.issues {
+        println!("{}", issue);
+    }
+    if !report.repairs.is_empty() {
+        save_game_file(Path::new(data_path), &game)?;
+        println!("Made {} repair(s) to {}", report.repairs.len(), data_path);
+    }
+
+    let errors = report.errors().count();
+    println!("{} error(s), {} warning(s)", errors, report.issues.len() - errors);
+    if errors > 0 {
+        return Err(format!("`{}` failed validation", data_path));
+    }
+
+    Ok(())
+}
+fn export(args: &[String]) -> Result<(), String> {
+    let (positionals, options) = parse_args(args, &[], &["--weapons-only", "--loadouts-only"])?;
+    let [data_path, out_path] = positionals[..] else { return Err(USAGE.to_string()); };
+
+    let weapons_only = options.iter().any(|(name, _)| *name == "--weapons-only");
+    let loadouts_only = options.iter().any(|(name, _)| *name == "--loadouts-only");
+    if weapons_only && loadouts_only {
+        return Err("`--weapons-only` and `--loadouts-only` can't be combined".to_string());
+    }
+
+    let game = load_game_file(Path::new(data_path))?;
+    let export = export_game_data(&game, !loadouts_only, !weapons_only);
+    std::fs::write(
+        out_path,
+        serde_json::to_string_pretty(&export).map_err(|e| format!("Failed to serialize export: {}", e))?
+    ).map_err(|e| format!("Failed to write {}: {}", out_path, e))?;
+
+    println!(
+        "Exported {} weapon(s) and {} categories to {}",
+        export.weapons.as_ref().map(|w| w.len()).unwrap_or(0),
+        export.categories.as_ref().map(|c| c.len()).unwrap_or(0),
+        out_path
+    );
+
+    Ok(())
+}
+fn import(args: &[String]) -> Result<(), String> {
+    let (positionals, _) = parse_args(args, &[], &[])?;
+    let [data_path, in_path] = positionals[..] else { return Err(USAGE.to_string()); };
+
+    let mut game = load_game_file(Path::new(data_path))?;
+    let data: GameDataExport = serde_json::from_str(
+        &std::fs::read_to_string(in_path).map_err(|e| format!("Failed to read {}: {}", in_path, e))?
+    ).map_err(|e| format!("Failed to parse {}: {}", in_path, e))?;
+
+    let summary = import_game_data(&mut game, data);
+
+    // Never write data the engine can't use
+    let errors: Vec<_> = validate_game(&game).into_iter()
+        .filter(|issue| issue.severity == IssueSeverity::Error)
+        .collect();
+    if !errors.is_empty() {
+        for error in &errors {
+            eprintln!("{}", error);
+        }
+        return Err(format!("Import would leave `{}` invalid, nothing was written", data_path));
+    }
+
+    save_game_file(Path::new(data_path), &game)?;
+    println!(
+        "Imported {} new and {} replaced weapon(s), {} new and {} replaced loadout(s)",
+        summary.weapons_added, summary.weapons_replaced, summary.loadouts_added, summary.loadouts_replaced
+    );
+
+    Ok(())
+}
+fn scale(args: &[String]) -> Result<(), String> {
+    let (positionals, options) = parse_args(args, &["--dx", "--dy", "--weapon"], &[])?;
+    let [data_path] = positionals[..] else { return Err(USAGE.to_string()); };
+
+    let dx_factor: f32 = option(&options, "--dx").map(|v| parse_number("--dx", v)).transpose()?.unwrap_or(1.0);
+    let dy_factor: f32 = option(&options, "--dy").map(|v| parse_number("--dy", v)).transpose()?.unwrap_or(1.0);
+    if !dx_factor.is_finite() || !dy_factor.is_finite() {
+        return Err("Scale factors must be finite".to_string());
+    }
+    let weapon_ids: Vec<String> = options.iter()
+        .filter(|(name, _)| *name == "--weapon")
+        .filter_map(|(_, value)| value.map(str::to_string))
+        .collect();
+
+    let mut game = load_game_file(Path::new(data_path))?;
+    let scaled = scale_weapons(&mut game, dx_factor, dy_factor, &weapon_ids)?;
+    save_game_file(Path::new(data_path), &game)?;
+
+    println!("Scaled {} weapon(s) by dx x{} and dy x{}", scaled, dx_factor, dy_factor);
+
+    Ok(())
+}
+fn simulate(args: &[String]) -> Result<(), String> {
+    let (positionals, options) = parse_args(args, &["--shots", "--config"], &[])?;
+    let [data_path, weapon_id] = positionals[..] else { return Err(USAGE.to_string()); };
+
+    let shots: u32 = option(&options, "--shots").map(|v| parse_number("--shots", v)).transpose()?.unwrap_or(30);
+    let config_path = option(&options, "--config").map(PathBuf::from);
+
+    let game = load_game_file(Path::new(data_path))?;
+    let config = load_config_file(config_path.as_deref())?;
+    let moves = simulate_weapon(&game, &config, weapon_id, shots)?;
+
+    println!("shot\tms\tdx\tdy");
+    for simulated in &moves {
+        println!("{}\t{:.3}\t{}\t{}", simulated.shot, simulated.at_ms, simulated.dx, simulated.dy);
+    }
+    let (dx_total, dy_total) = moves.iter().fold((0, 0), |(dx, dy), m| (dx + m.dx, dy + m.dy));
+    println!("total\t\t{}\t{}", dx_total, dy_total);
+
+    Ok(())
+}
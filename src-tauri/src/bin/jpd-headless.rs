@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use clc_jpd_lib::{default_config_dir, run_headless, HeadlessOptions};
+
+const USAGE: &str = "Usage: jpd-headless [--config-dir <path>] [--game <name>]";
+
+fn main() {
+    let mut config_dir: Option<PathBuf> = None;
+    let mut game: Option<String> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config-dir" => config_dir = args.next().map(PathBuf::from),
+            "--game" => game = args.next(),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            },
+            _ => {
+                eprintln!("Unknown argument `{}`\n{}", arg, USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let Some(config_dir) = config_dir.or_else(default_config_dir) else {
+        eprintln!("Could not determine the config directory, pass --config-dir\n{}", USAGE);
+        std::process::exit(2);
+    };
+
+    if let Err(e) = run_headless(HeadlessOptions { config_dir, game }) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -1,7 +1,8 @@
 use tauri::ipc::Channel;
 use tauri_plugin_updater::UpdaterExt;
 
-use crate::types::{AppEvent, AppState};
+use crate::types::{AppState, EventEnvelope};
+use crate::worker::RecoilWorkerStatus;
 
 pub async fn update(app: tauri::AppHandle) -> tauri_plugin_updater::Result<()> {
     if let Some(update) = app.updater()?.check().await? {
@@ -75,11 +76,64 @@ pub async fn restart_app(
 #[tauri::command]
 pub async fn start_channel_reads (
     state: tauri::State<'_, AppState>,
-    channel: Channel<AppEvent>,
+    webview: tauri::Webview,
+    channel: Channel<EventEnvelope>,
 ) -> Result<String, String> {
-    while let Ok(event) = state.events_channel_reciever.lock().recv() {
-        channel.send(event).expect("Failed to send event");
+    // A reloaded webview starts a new read, so drop whatever the old page left behind
+    let subscriber_name = format!("webview:{}", webview.label());
+    let replaced = state.events.unsubscribe(&subscriber_name);
+    if replaced > 0 {
+        println!("Replaced {} stale event subscriber(s) for `{}`", replaced, subscriber_name);
     }
 
-    Err(String::from("Channel reads closed early?"))
-}
\ No newline at end of file
+    let mut subscriber = state.events.subscribe(subscriber_name);
+    while let Some(event) = subscriber.recv().await {
+        if let Err(e) = channel.send(EventEnvelope::from(event)) {
+            // The channel is gone along with its webview, stop reading
+            eprintln!("Event subscriber `{}` could not deliver event, unsubscribing: {}", subscriber.name(), e);
+            return Ok(String::from("Channel closed"));
+        }
+    }
+
+    Ok(String::from("Channel reads stopped"))
+}
+#[tauri::command]
+pub async fn stop_channel_reads (
+    state: tauri::State<'_, AppState>,
+    webview: tauri::Webview,
+) -> Result<usize, String> {
+    let stopped = state.events.unsubscribe(&format!("webview:{}", webview.label()));
+    println!("Stopped {} event subscriber(s) for webview `{}`", stopped, webview.label());
+
+    Ok(stopped)
+}#[tauri::command]
+pub fn get_recoil_worker_status (
+    state: tauri::State<'_, AppState>,
+) -> RecoilWorkerStatus {
+    state.recoil_worker.status()
+}
+#[tauri::command]
+pub fn start_recoil_worker (
+    state: tauri::State<'_, AppState>,
+) -> Result<RecoilWorkerStatus, String> {
+    state.recoil_worker.start(&state)?;
+
+    Ok(state.recoil_worker.status())
+}
+#[tauri::command]
+pub async fn stop_recoil_worker (
+    state: tauri::State<'_, AppState>,
+) -> Result<RecoilWorkerStatus, String> {
+    // Waits for a spray in progress to be aborted
+    state.recoil_worker.stop()?;
+
+    Ok(state.recoil_worker.status())
+}
+#[tauri::command]
+pub async fn restart_recoil_worker (
+    state: tauri::State<'_, AppState>,
+) -> Result<RecoilWorkerStatus, String> {
+    state.recoil_worker.restart(&state)?;
+
+    Ok(state.recoil_worker.status())
+}
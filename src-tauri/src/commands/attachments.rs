@@ -0,0 +1,77 @@
+use super::loadouts::edit_game;
+use crate::types::{AppState, Attachment, Game};
+
+/// Add an attachment to the selected game's catalogue, or replace the one with the same ID
+#[tauri::command]
+pub fn save_attachment(
+    state: tauri::State<'_, AppState>,
+    attachment_id: String,
+    attachment: Attachment
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let attachment_id = attachment_id.trim().to_string();
+        if attachment_id.is_empty() {
+            return Err("Attachment ID can't be empty".to_string());
+        }
+        if attachment.name.trim().is_empty() {
+            return Err("Attachment name can't be empty".to_string());
+        }
+        let values = [
+            attachment.horizontal_multiplier,
+            attachment.vertical_multiplier,
+            attachment.dx_offset,
+            attachment.dy_offset,
+        ];
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err("Attachment multipliers and offsets must be finite numbers".to_string());
+        }
+
+        let change = match game.attachments.insert(attachment_id.clone(), attachment) {
+            Some(_) => format!("Updated attachment `{}`", attachment_id),
+            None => format!("Created attachment `{}`", attachment_id),
+        };
+
+        Ok(change)
+    })
+}
+/// Delete an attachment, refusing while loadouts still use it unless `remove_references`
+/// is set, in which case it's removed from those loadouts as well
+#[tauri::command]
+pub fn delete_attachment(
+    state: tauri::State<'_, AppState>,
+    attachment_id: String,
+    remove_references: bool
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        if !game.attachments.contains_key(&attachment_id) {
+            return Err(format!("Attachment `{}` not found", attachment_id));
+        }
+
+        let users: Vec<String> = game.categories.iter()
+            .flatten()
+            .flat_map(|category| category.loadouts.iter().map(move |loadout| (category, loadout)))
+            .flat_map(|(category, loadout)| loadout.slots.iter().map(move |slot| (category, loadout, slot)))
+            .flat_map(|(category, loadout, slot)| slot.attachments.iter()
+                .filter(|(_, attachment_ids)| attachment_ids.contains(&attachment_id))
+                .map(move |(weapon_id, _)| format!("{}/{}/{}", category.name, loadout.name, weapon_id)))
+            .collect();
+        if !users.is_empty() && !remove_references {
+            return Err(format!("Attachment `{}` is still used by {} weapon(s): {}", attachment_id, users.len(), users.join(", ")));
+        }
+
+        game.attachments.remove(&attachment_id);
+        game.removed.attachments.insert(attachment_id.clone());
+        for slot in game.categories.iter_mut()
+            .flatten()
+            .flat_map(|category| category.loadouts.iter_mut())
+            .flat_map(|loadout| loadout.slots.iter_mut())
+        {
+            for attachment_ids in slot.attachments.values_mut() {
+                attachment_ids.retain(|id| *id != attachment_id);
+            }
+            slot.attachments.retain(|_, attachment_ids| !attachment_ids.is_empty());
+        }
+
+        Ok(format!("Deleted attachment `{}`, removing it from {} weapon(s)", attachment_id, users.len()))
+    })
+}
@@ -1,4 +1,12 @@
-use crate::{load_games, save_data, types::{AppState, Game, GlobalConfig, LoadedGames, Weapon}};
+use crate::{
+    local_api, refresh_games, save_data,
+    motion::{MAX_TICK_RATE, MIN_TICK_RATE},
+    sensitivity::{self, SensitivityFormula, SensitivityProfile, SensitivityResult},
+    stance::{Stance, StanceKeyMode},
+    game_data::resolved_weapon,
+    types::{AppState, EffectiveConfig, Game, GlobalConfig, KeybindConfig, Weapon, WeaponEntry},
+    winapi::reserved_key_name,
+};
 
 #[tauri::command]
 pub fn change_grid_layout(
@@ -26,21 +34,14 @@ pub async fn reset_config_from_server(
         println!("Cleared all local game data from: {}", games_dir_path.display());
     }
     
-    // Now reload fresh data from the server (this will only use server data, no local merging)
-    let LoadedGames { game_data } = load_games(config_dir).await?;
+    // Now reload fresh data from the server (this will only use server data, no local merging),
+    // checking it and keeping the selection where the entries still exist
+    refresh_games(&state).await?;
+    let games = state.games.read_arc().clone();
     
-    // Update the application state with the fresh data
-    *state.games.write_arc() = game_data.clone();
+    println!("Successfully reset all game configurations from server. Loaded {} games.", games.len());
     
-    // Reset current indices to 0 since the game list may have changed
-    state.current_game_index.store(0, std::sync::atomic::Ordering::Relaxed);
-    state.current_category_index.store(0, std::sync::atomic::Ordering::Relaxed);
-    state.current_loadout_index.store(0, std::sync::atomic::Ordering::Relaxed);
-    state.current_weapon_index.store(0, std::sync::atomic::Ordering::Relaxed);
-    
-    println!("Successfully reset all game configurations from server. Loaded {} games.", game_data.len());
-    
-    Ok(game_data)
+    Ok(games)
 }
 #[tauri::command]
 pub fn change_weapon_config(
@@ -51,16 +52,21 @@ pub fn change_weapon_config(
     new_value: serde_json::Value
 ) -> Result<Vec<Game>, String> {
     // Load the current game, category, loadout, and weapon indices
-    let current_game_index = state.current_game_index.load(std::sync::atomic::Ordering::Relaxed);
+    let current_game_index = state.selection.get().game;
     
-    match state.games.write_arc()
+    let mut games = state.games.write_arc();
+    let weapons = games
         .get_mut(current_game_index).ok_or(format!("Game index {} not found", current_game_index))?
         .weapons.as_mut()
-        .ok_or(format!("No weapons found in game `{}`", current_game_index))?
-        .get_mut(&weapon_id)
+        .ok_or(format!("No weapons found in game `{}`", current_game_index))?;
+    let previous = weapons.get(&weapon_id)
+        .cloned()
+        .ok_or(format!("Weapon ID `{}` not found in game `{}`", weapon_id, current_game_index))?;
+
+    match weapons.get_mut(&weapon_id)
         .ok_or(format!("Weapon ID `{}` not found in game `{}`", weapon_id, current_game_index))?
     {
-        Weapon::SingleFire(weapon_config) => {
+        WeaponEntry::Weapon(Weapon::SingleFire(weapon_config)) => {
             match field.as_str() {
                 "name" => weapon_config.name = new_value.as_str().ok_or("Invalid value for name")?.to_string(),
                 "description" => weapon_config.description = new_value.as_str().map(|s| s.to_string()),
@@ -74,7 +80,7 @@ pub fn change_weapon_config(
                 _ => return Err(format!("Unknown field: {}", field)),
             }
         },
-        Weapon::FullAutoStandard(weapon_config) => {
+        WeaponEntry::Weapon(Weapon::FullAutoStandard(weapon_config)) => {
             match field.as_str() {
                 "name" => weapon_config.name = new_value.as_str().ok_or("Invalid value for name")?.to_string(),
                 "description" => weapon_config.description = new_value.as_str().map(|s| s.to_string()),
@@ -87,7 +93,7 @@ pub fn change_weapon_config(
                 _ => return Err(format!("Unknown field: {}", field)),
             }
         },
-        Weapon::SingleShot(weapon_config) => {
+        WeaponEntry::Weapon(Weapon::SingleShot(weapon_config)) => {
             match field.as_str() {
                 "name" => weapon_config.name = new_value.as_str().ok_or("Invalid value for name")?.to_string(),
                 "description" => weapon_config.description = new_value.as_str().map(|s| s.to_string()),
@@ -98,9 +104,24 @@ pub fn change_weapon_config(
 
                 _ => return Err(format!("Unknown field: {}", field)),
             }
+        },
+        // Variants override single fields of their base, `null` goes back to the base's value
+        WeaponEntry::Variant(variant) => {
+            match field.as_str() {
+                "base" => variant.base = new_value.as_str().ok_or("Invalid value for base")?.to_string(),
+                _ if new_value.is_null() => { variant.overrides.remove(&field); },
+                _ => { variant.overrides.insert(field.clone(), new_value.clone()); },
+            }
         }
     }
 
+    // Catches overrides of the wrong type and bases that are missing or loop back
+    if let Err(e) = resolved_weapon(weapons, &weapon_id) {
+        weapons.insert(weapon_id.clone(), previous);
+        return Err(e);
+    }
+    drop(games);
+
     // Save the updated game data
     save_data(&state).map_err(|e| format!("Failed to save game data: {}", e))?;
     println!("Updated field `{}` for weapon `{}` in game `{}` to `{}`",
@@ -110,27 +131,76 @@ pub fn change_weapon_config(
     // Return the updated games list
     Ok(state.games.read_arc().clone())
 }
+/// Check that every weapon slot key is unique and not taken by a hotkey
+/// the input listener handles first, which would keep the slot from ever being selected
+fn check_slot_keys(
+    keybinds: &KeybindConfig
+) -> Result<(), String> {
+    let slot_keys: Vec<char> = keybinds.slot_keys().collect();
+    for (index, key) in slot_keys.iter().enumerate() {
+        let slot = index + 1;
+        if let Some(other) = slot_keys[..index].iter().position(|other| other.eq_ignore_ascii_case(key)) {
+            return Err(format!("Slot {} key `{}` is already used by slot {}", slot, key, other + 1));
+        }
+        if key.eq_ignore_ascii_case(&keybinds.crouch) || key.eq_ignore_ascii_case(&keybinds.prone) {
+            return Err(format!("Slot {} key `{}` is already used as a stance key", slot, key));
+        }
+        if let Some(name) = reserved_key_name(*key) {
+            return Err(format!("Slot {} key `{}` is the {} hotkey", slot, key, name));
+        }
+    }
+
+    Ok(())
+}
 #[tauri::command]
 pub async fn change_setting (
     state: tauri::State<'_, AppState>,
     setting: String,
     value: serde_json::Value
 ) -> Result<GlobalConfig, String> {
-    match setting.as_str() {
-        "primary_weapon" | "secondary_weapon" | "alternative_fire" => {
-            let new_value = value.as_str().ok_or("Invalid value! Must be a string.")?;
-            if new_value.len() != 1 {
-                return Err("Primary weapon must be a single character".to_string());
-            }
+    {
+        let mut global_config = state.global_config.write_arc();
+        let mut keybinds = global_config.keybinds.clone();
+        match setting.as_str() {
+            "primary_weapon" | "secondary_weapon" | "alternative_fire" => {
+                let new_value = value.as_str().ok_or("Invalid value! Must be a string.")?;
+                if new_value.len() != 1 {
+                    return Err("Primary weapon must be a single character".to_string());
+                }
 
-            match setting.as_str() {
-                "primary_weapon" => state.global_config.write_arc().keybinds.primary_weapon = new_value.chars().next().unwrap(),
-                "secondary_weapon" => state.global_config.write_arc().keybinds.secondary_weapon = new_value.chars().next().unwrap(),
-                "alternative_fire" => state.global_config.write_arc().keybinds.alternative_fire = new_value.chars().next().unwrap(),
-                _ => unreachable!(),
-            }
-        },
-        _ => return Err(format!("Unknown setting: {}", setting)),
+                match setting.as_str() {
+                    "primary_weapon" => keybinds.primary_weapon = new_value.chars().next().unwrap(),
+                    "secondary_weapon" => keybinds.secondary_weapon = new_value.chars().next().unwrap(),
+                    "alternative_fire" => keybinds.alternative_fire = new_value.chars().next().unwrap(),
+                    _ => unreachable!(),
+                }
+            },
+            "extra_slot_keys" => {
+                // One character per slot, starting from the third
+                let new_value = value.as_str().ok_or("Invalid value! Must be a string.")?;
+                keybinds.extra_slot_keys = new_value.chars().collect();
+            },
+            "crouch" | "prone" => {
+                let new_value = value.as_str().ok_or("Invalid value! Must be a string.")?;
+                if new_value.chars().count() != 1 {
+                    return Err(format!("`{}` must be a single character", setting));
+                }
+
+                match setting.as_str() {
+                    "crouch" => keybinds.crouch = new_value.chars().next().unwrap(),
+                    "prone" => keybinds.prone = new_value.chars().next().unwrap(),
+                    _ => unreachable!(),
+                }
+            },
+            "stance_key_mode" => {
+                keybinds.stance_key_mode = serde_json::from_value::<StanceKeyMode>(value.clone())
+                    .map_err(|e| format!("Invalid stance key mode: {}", e))?;
+            },
+            _ => return Err(format!("Unknown setting: {}", setting)),
+        }
+
+        check_slot_keys(&keybinds)?;
+        global_config.keybinds = keybinds;
     }
 
     println!("Updated setting `{}` to `{}`", setting, value);
@@ -139,6 +209,248 @@ pub async fn change_setting (
     Ok(state.global_config.read_arc().clone())
 }
 #[tauri::command]
+pub async fn change_game_setting (
+    state: tauri::State<'_, AppState>,
+    game_name: String,
+    setting: String,
+    value: serde_json::Value
+) -> Result<GlobalConfig, String> {
+    if !state.games.read_arc().iter().any(|g| g.name == game_name) {
+        return Err(format!("Unknown game: {}", game_name));
+    }
+
+    {
+        let mut global_config = state.global_config.write_arc();
+        // Edited on a copy, so a rejected value doesn't leave an empty override entry behind
+        let mut game_settings = global_config.game_settings
+            .get(&game_name)
+            .cloned()
+            .unwrap_or_default();
+
+        // A `null` value clears the override so the global value is used again
+        match setting.as_str() {
+            "primary_weapon" | "secondary_weapon" | "alternative_fire" => {
+                let new_value = if value.is_null() {
+                    None
+                } else {
+                    let new_value = value.as_str().ok_or("Invalid value! Must be a string.")?;
+                    if new_value.chars().count() != 1 {
+                        return Err(format!("`{}` must be a single character", setting));
+                    }
+                    new_value.chars().next()
+                };
+
+                match setting.as_str() {
+                    "primary_weapon" => game_settings.keybinds.primary_weapon = new_value,
+                    "secondary_weapon" => game_settings.keybinds.secondary_weapon = new_value,
+                    "alternative_fire" => game_settings.keybinds.alternative_fire = new_value,
+                    _ => unreachable!(),
+                }
+            },
+            "extra_slot_keys" => {
+                game_settings.keybinds.extra_slot_keys = if value.is_null() {
+                    None
+                } else {
+                    Some(value.as_str().ok_or("Invalid value! Must be a string.")?.chars().collect())
+                };
+            },
+            "crouch" | "prone" => {
+                let new_value = if value.is_null() {
+                    None
+                } else {
+                    let new_value = value.as_str().ok_or("Invalid value! Must be a string.")?;
+                    if new_value.chars().count() != 1 {
+                        return Err(format!("`{}` must be a single character", setting));
+                    }
+                    new_value.chars().next()
+                };
+
+                match setting.as_str() {
+                    "crouch" => game_settings.keybinds.crouch = new_value,
+                    "prone" => game_settings.keybinds.prone = new_value,
+                    _ => unreachable!(),
+                }
+            },
+            "stance_key_mode" => {
+                game_settings.keybinds.stance_key_mode = if value.is_null() {
+                    None
+                } else {
+                    Some(serde_json::from_value(value.clone()).map_err(|e| format!("Invalid stance key mode: {}", e))?)
+                };
+            },
+            "require_right_hold" => {
+                game_settings.keybinds.require_right_hold = if value.is_null() {
+                    None
+                } else {
+                    Some(value.as_bool().ok_or("Invalid value! Must be a boolean.")?)
+                };
+            },
+            "horizontal_multiplier" | "vertical_multiplier" | "acog_horizontal_multiplier" | "acog_vertical_multiplier" | "sensitivity" => {
+                let new_value = if value.is_null() {
+                    None
+                } else {
+                    let new_value = value.as_f64().ok_or("Invalid value! Must be a number.")? as f32;
+                    if new_value <= 0.0 {
+                        return Err(format!("Invalid {}: {}", setting, new_value));
+                    }
+                    Some(new_value)
+                };
+
+                match setting.as_str() {
+                    "horizontal_multiplier" => game_settings.mouse_config.horizontal_multiplier = new_value,
+                    "vertical_multiplier" => game_settings.mouse_config.vertical_multiplier = new_value,
+                    "acog_horizontal_multiplier" => game_settings.mouse_config.acog_horizontal_multiplier = new_value,
+                    "acog_vertical_multiplier" => game_settings.mouse_config.acog_vertical_multiplier = new_value,
+                    "sensitivity" => game_settings.mouse_config.sensitivity = new_value,
+                    _ => unreachable!(),
+                }
+            },
+            _ => return Err(format!("Unknown setting: {}", setting)),
+        }
+
+        check_slot_keys(&game_settings.keybinds.apply(&global_config.keybinds))?;
+
+        global_config.game_settings.insert(game_name.clone(), game_settings);
+    }
+
+    println!("Updated setting `{}` for game `{}` to `{}`", setting, game_name, value);
+    save_data(&state).map_err(|e| format!("Failed to save game data: {}", e))?;
+
+    Ok(state.global_config.read_arc().clone())
+}
+/// Set how much a game's weapons pull while crouching or prone, standing is never scaled
+#[tauri::command]
+pub async fn change_stance_modifier (
+    state: tauri::State<'_, AppState>,
+    game_name: String,
+    stance: Stance,
+    horizontal_multiplier: f32,
+    vertical_multiplier: f32
+) -> Result<GlobalConfig, String> {
+    if !state.games.read_arc().iter().any(|g| g.name == game_name) {
+        return Err(format!("Unknown game: {}", game_name));
+    }
+    if !(horizontal_multiplier.is_finite() && horizontal_multiplier > 0.0 && vertical_multiplier.is_finite() && vertical_multiplier > 0.0) {
+        return Err(format!("Invalid stance multipliers: {}, {}", horizontal_multiplier, vertical_multiplier));
+    }
+    if stance == Stance::Standing {
+        return Err("Standing has no multipliers".to_string());
+    }
+
+    {
+        let mut global_config = state.global_config.write_arc();
+        let stance_modifiers = &mut global_config.game_settings
+            .entry(game_name.clone())
+            .or_default()
+            .stance_modifiers;
+
+        if let Some(multipliers) = stance_modifiers.get_mut(stance) {
+            multipliers.horizontal_multiplier = horizontal_multiplier;
+            multipliers.vertical_multiplier = vertical_multiplier;
+        }
+    }
+
+    println!("Changed {:?} multipliers for game `{}` to {}, {}", stance, game_name, horizontal_multiplier, vertical_multiplier);
+    save_data(&state).map_err(|e| format!("Failed to save game data: {}", e))?;
+
+    Ok(state.global_config.read_arc().clone())
+}
+#[tauri::command]
+pub fn get_effective_config_for_game (
+    state: tauri::State<'_, AppState>,
+    game_name: String
+) -> Result<EffectiveConfig, String> {
+    let games = state.games.read_arc();
+    let game = games.iter()
+        .find(|g| g.name == game_name)
+        .ok_or(format!("Unknown game: {}", game_name))?;
+
+    Ok(state.global_config.read_arc().effective(Some(game)))
+}
+#[tauri::command]
+pub async fn calculate_sensitivity (
+    state: tauri::State<'_, AppState>,
+    game_name: Option<String>,
+    reference: SensitivityProfile,
+    user: SensitivityProfile,
+    optic_zoom: f32,
+    formula: Option<SensitivityFormula>,
+    apply: bool
+) -> Result<SensitivityResult, String> {
+    let mut reference = reference;
+    let (formula, dpi_scale) = {
+        let games = state.games.read_arc();
+        let global_config = state.global_config.read_arc();
+        match &game_name {
+            Some(name) => {
+                let game = games.iter()
+                    .find(|g| &g.name == name)
+                    .ok_or(format!("Unknown game: {}", name))?;
+
+                // The engine rescales from the game's reference values, so the calculator must use the same ones
+                if let Some(reference_dpi) = game.reference_dpi {
+                    reference.dpi = reference_dpi as f32;
+                }
+                if let Some(reference_sensitivity) = game.reference_sensitivity {
+                    reference.sensitivity = reference_sensitivity;
+                }
+
+                // Fall back to the formula shipped with the game data, then to linear
+                (
+                    formula.or(game.sensitivity_formula).unwrap_or_default(),
+                    global_config.effective(Some(game)).dpi_scale,
+                )
+            },
+            None => {
+                // Global multipliers are used by every game, some of which may already rescale on their own
+                if apply {
+                    if let Some(game) = games.iter().find(|g| global_config.effective(Some(g)).dpi_scale != 1.0) {
+                        return Err(format!("`{}` already rescales from its reference DPI and sensitivity, apply the multipliers to that game instead", game.name));
+                    }
+                }
+
+                (formula.unwrap_or_default(), 1.0)
+            }
+        }
+    };
+
+    let result = sensitivity::calculate(&reference, &user, optic_zoom, formula, dpi_scale)?;
+    println!("Calculated sensitivity multipliers ({:?}): {:?}", formula, result);
+
+    if !apply {
+        return Ok(result);
+    }
+
+    {
+        let mut global_config = state.global_config.write_arc();
+        match &game_name {
+            Some(game_name) => {
+                // Store as overrides so other games keep their own multipliers
+                let overrides = &mut global_config.game_settings
+                    .entry(game_name.clone())
+                    .or_default()
+                    .mouse_config;
+                overrides.horizontal_multiplier = Some(result.horizontal_multiplier);
+                overrides.vertical_multiplier = Some(result.vertical_multiplier);
+                overrides.acog_horizontal_multiplier = Some(result.acog_horizontal_multiplier);
+                overrides.acog_vertical_multiplier = Some(result.acog_vertical_multiplier);
+            },
+            None => {
+                let mouse_config = &mut global_config.mouse_config;
+                mouse_config.horizontal_multiplier = result.horizontal_multiplier;
+                mouse_config.vertical_multiplier = result.vertical_multiplier;
+                mouse_config.acog_horizontal_multiplier = result.acog_horizontal_multiplier;
+                mouse_config.acog_vertical_multiplier = result.acog_vertical_multiplier;
+            }
+        }
+    }
+
+    save_data(&state).map_err(|e| format!("Failed to save game data: {}", e))?;
+    println!("Applied calculated sensitivity multipliers to {}", game_name.as_deref().unwrap_or("global config"));
+
+    Ok(result)
+}
+#[tauri::command]
 pub async fn change_horizontal_multiplier (
     state: tauri::State<'_, AppState>,
     new_multiplier: f32
@@ -208,4 +520,69 @@ pub async fn change_scroll_wheel_weapon_swap (
     println!("Changed scroll wheel weapon swap to {}", enabled);
 
     Ok(state.global_config.read_arc().clone())
-}
\ No newline at end of file
+}
+#[tauri::command]
+pub async fn change_mouse_dpi (
+    state: tauri::State<'_, AppState>,
+    new_dpi: Option<u32>
+) -> Result<GlobalConfig, String> {
+    if new_dpi == Some(0) {
+        return Err("Invalid mouse DPI: 0".to_string());
+    }
+
+    state.global_config.write_arc().mouse_config.dpi = new_dpi;
+    save_data(&state).map_err(|e| format!("Failed to save game data: {}", e))?;
+    println!("Changed mouse DPI to {:?}", new_dpi);
+
+    Ok(state.global_config.read_arc().clone())
+}
+#[tauri::command]
+pub async fn change_tick_rate (
+    state: tauri::State<'_, AppState>,
+    new_tick_rate: u32
+) -> Result<GlobalConfig, String> {
+    if !(MIN_TICK_RATE..=MAX_TICK_RATE).contains(&new_tick_rate) {
+        return Err(format!("Invalid tick rate: {}, must be between {} and {} Hz", new_tick_rate, MIN_TICK_RATE, MAX_TICK_RATE));
+    }
+
+    state.global_config.write_arc().mouse_config.tick_rate = new_tick_rate;
+    save_data(&state).map_err(|e| format!("Failed to save game data: {}", e))?;
+    println!("Changed tick rate to {} Hz", new_tick_rate);
+
+    Ok(state.global_config.read_arc().clone())
+}
+#[tauri::command]
+pub async fn change_local_api_settings (
+    state: tauri::State<'_, AppState>,
+    enabled: bool,
+    port: u16
+) -> Result<GlobalConfig, String> {
+    if port == 0 {
+        return Err("Invalid local API port: 0".to_string());
+    }
+
+    {
+        let mut global_config = state.global_config.write_arc();
+        global_config.local_api.enabled = enabled;
+        global_config.local_api.port = port;
+    }
+    save_data(&state).map_err(|e| format!("Failed to save game data: {}", e))?;
+    println!("Changed local API to {} on port {}", if enabled { "enabled" } else { "disabled" }, port);
+
+    local_api::restart(&state);
+
+    Ok(state.global_config.read_arc().clone())
+}
+#[tauri::command]
+pub async fn regenerate_local_api_token (
+    state: tauri::State<'_, AppState>
+) -> Result<GlobalConfig, String> {
+    state.global_config.write_arc().local_api.token = local_api::generate_token();
+    save_data(&state).map_err(|e| format!("Failed to save game data: {}", e))?;
+    println!("Regenerated local API token");
+
+    // Running clients must reconnect with the new token
+    local_api::restart(&state);
+
+    Ok(state.global_config.read_arc().clone())
+}
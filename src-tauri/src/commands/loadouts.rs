@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+
+use crate::{
+    assign_ids,
+    save_data,
+    selection::revalidate_selection,
+    types::{AppEvent, AppState, Category, Game, Loadout, WeaponSlot},
+};
+
+/// Apply `edit` to a copy of the selected game and, if it succeeds, replace the game with it,
+/// then re-find the selection, save and notify the frontend
+///
+/// `edit` returns a description of the change for the log. New categories and loadouts
+/// are given IDs from their names.
+pub(super) fn edit_game(
+    state: &AppState,
+    edit: impl FnOnce(&mut Game) -> Result<String, String>,
+) -> Result<Vec<Game>, String> {
+    let game_index = state.selection.get().game;
+
+    {
+        let mut games = state.games.write_arc();
+        let game = games.get_mut(game_index)
+            .ok_or(format!("Game index {} not found", game_index))?;
+
+        let mut edited = game.clone();
+        let change = edit(&mut edited)?;
+        assign_ids(&mut edited);
+
+        println!("{} in game `{}`", change, edited.name);
+        *game = edited;
+    }
+
+    // Reordering or deleting may have moved the selected category or loadout
+    revalidate_selection(state);
+    save_data(state).map_err(|e| format!("Failed to save data: {}", e))?;
+
+    let games = state.games.read_arc().clone();
+    state.events.publish(AppEvent::UpdatedGames {
+        games: games.clone(),
+    });
+
+    Ok(games)
+}
+
+fn categories_mut(
+    game: &mut Game
+) -> Result<&mut Vec<Category>, String> {
+    let name = game.name.clone();
+    game.categories.as_mut()
+        .ok_or(format!("Game `{}` does not have data loaded", name))
+}
+fn category_index(
+    categories: &[Category],
+    category_id: &str
+) -> Result<usize, String> {
+    categories.iter()
+        .position(|category| category.id == category_id)
+        .ok_or(format!("Category `{}` not found", category_id))
+}
+/// Category and loadout index of the loadout with ID `loadout_id`
+fn loadout_position(
+    categories: &[Category],
+    loadout_id: &str
+) -> Result<(usize, usize), String> {
+    categories.iter()
+        .enumerate()
+        .find_map(|(category_index, category)| category.loadouts.iter()
+            .position(|loadout| loadout.id == loadout_id)
+            .map(|loadout_index| (category_index, loadout_index)))
+        .ok_or(format!("Loadout `{}` not found", loadout_id))
+}
+/// Trim `name` and check it's neither empty nor already used by a sibling
+fn check_name<'a>(
+    kind: &str,
+    name: &str,
+    mut siblings: impl Iterator<Item = &'a str>
+) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("{} name can't be empty", kind));
+    }
+    if siblings.any(|sibling| sibling.trim().eq_ignore_ascii_case(name)) {
+        return Err(format!("{} `{}` already exists", kind, name));
+    }
+
+    Ok(name.to_string())
+}
+/// Copy with the IDs cleared, so `assign_ids` generates new ones
+fn copy_loadout(
+    loadout: &Loadout,
+    name: String
+) -> Loadout {
+    Loadout {
+        id: String::new(),
+        name,
+        ..loadout.clone()
+    }
+}
+
+#[tauri::command]
+pub fn create_category(
+    state: tauri::State<'_, AppState>,
+    name: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let name = check_name("Category", &name, categories.iter().map(|category| category.name.as_str()))?;
+
+        categories.push(Category {
+            id: String::new(),
+            name: name.clone(),
+            loadouts: Vec::new(),
+        });
+
+        Ok(format!("Created category `{}`", name))
+    })
+}
+#[tauri::command]
+pub fn rename_category(
+    state: tauri::State<'_, AppState>,
+    category_id: String,
+    name: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let index = category_index(categories, &category_id)?;
+        let name = check_name("Category", &name, categories.iter()
+            .enumerate()
+            .filter(|(i, _)| *i != index)
+            .map(|(_, category)| category.name.as_str()))?;
+
+        categories[index].name = name.clone();
+
+        Ok(format!("Renamed category `{}` to `{}`", category_id, name))
+    })
+}
+#[tauri::command]
+pub fn delete_category(
+    state: tauri::State<'_, AppState>,
+    category_id: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let index = category_index(categories, &category_id)?;
+
+        let category = categories.remove(index);
+        game.removed.categories.insert(category.id.clone());
+        game.removed.loadouts.extend(category.loadouts.iter().map(|loadout| loadout.id.clone()));
+
+        Ok(format!("Deleted category `{}` with {} loadouts", category.name, category.loadouts.len()))
+    })
+}
+/// Copy a category and all of its loadouts, placing the copy right after it
+#[tauri::command]
+pub fn duplicate_category(
+    state: tauri::State<'_, AppState>,
+    category_id: String,
+    name: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let index = category_index(categories, &category_id)?;
+        let name = check_name("Category", &name, categories.iter().map(|category| category.name.as_str()))?;
+
+        let original = &categories[index];
+        let copy = Category {
+            id: String::new(),
+            name: name.clone(),
+            loadouts: original.loadouts.iter()
+                .map(|loadout| copy_loadout(loadout, loadout.name.clone()))
+                .collect(),
+        };
+        categories.insert(index + 1, copy);
+
+        Ok(format!("Duplicated category `{}` as `{}`", category_id, name))
+    })
+}
+/// Move a category to `new_index`, clamped to the end of the list
+#[tauri::command]
+pub fn move_category(
+    state: tauri::State<'_, AppState>,
+    category_id: String,
+    new_index: usize
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let index = category_index(categories, &category_id)?;
+
+        let category = categories.remove(index);
+        let new_index = new_index.min(categories.len());
+        categories.insert(new_index, category);
+
+        Ok(format!("Moved category `{}` to index {}", category_id, new_index))
+    })
+}
+
+/// Create an empty loadout with a primary and secondary slot
+#[tauri::command]
+pub fn create_loadout(
+    state: tauri::State<'_, AppState>,
+    category_id: String,
+    name: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let index = category_index(categories, &category_id)?;
+        let category = &mut categories[index];
+        let name = check_name("Loadout", &name, category.loadouts.iter().map(|loadout| loadout.name.as_str()))?;
+
+        let empty_slot = |name: &str| WeaponSlot {
+            name: name.to_string(),
+            weapons: Vec::new(),
+            selected: 0,
+            attachments: HashMap::new(),
+        };
+        category.loadouts.push(Loadout {
+            id: String::new(),
+            name: name.clone(),
+            icon_url: None,
+            icon_only: false,
+            slots: vec![empty_slot("Primary"), empty_slot("Secondary")],
+        });
+
+        Ok(format!("Created loadout `{}` in category `{}`", name, category.name))
+    })
+}
+#[tauri::command]
+pub fn rename_loadout(
+    state: tauri::State<'_, AppState>,
+    loadout_id: String,
+    name: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let (category_index, loadout_index) = loadout_position(categories, &loadout_id)?;
+        let loadouts = &mut categories[category_index].loadouts;
+        let name = check_name("Loadout", &name, loadouts.iter()
+            .enumerate()
+            .filter(|(i, _)| *i != loadout_index)
+            .map(|(_, loadout)| loadout.name.as_str()))?;
+
+        loadouts[loadout_index].name = name.clone();
+
+        Ok(format!("Renamed loadout `{}` to `{}`", loadout_id, name))
+    })
+}
+#[tauri::command]
+pub fn delete_loadout(
+    state: tauri::State<'_, AppState>,
+    loadout_id: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let (category_index, loadout_index) = loadout_position(categories, &loadout_id)?;
+
+        let loadout = categories[category_index].loadouts.remove(loadout_index);
+        game.removed.loadouts.insert(loadout.id.clone());
+
+        Ok(format!("Deleted loadout `{}`", loadout.name))
+    })
+}
+/// Copy a loadout within its category, placing the copy right after it
+#[tauri::command]
+pub fn duplicate_loadout(
+    state: tauri::State<'_, AppState>,
+    loadout_id: String,
+    name: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let (category_index, loadout_index) = loadout_position(categories, &loadout_id)?;
+        let loadouts = &mut categories[category_index].loadouts;
+        let name = check_name("Loadout", &name, loadouts.iter().map(|loadout| loadout.name.as_str()))?;
+
+        let copy = copy_loadout(&loadouts[loadout_index], name.clone());
+        loadouts.insert(loadout_index + 1, copy);
+
+        Ok(format!("Duplicated loadout `{}` as `{}`", loadout_id, name))
+    })
+}
+/// Move a loadout to `new_index` in the category `category_id`, which may be another
+/// category than its current one
+#[tauri::command]
+pub fn move_loadout(
+    state: tauri::State<'_, AppState>,
+    loadout_id: String,
+    category_id: String,
+    new_index: usize
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let (from_category, loadout_index) = loadout_position(categories, &loadout_id)?;
+        let to_category = category_index(categories, &category_id)?;
+
+        if from_category != to_category {
+            check_name("Loadout", &categories[from_category].loadouts[loadout_index].name, categories[to_category].loadouts.iter()
+                .map(|loadout| loadout.name.as_str()))?;
+        }
+
+        let loadout = categories[from_category].loadouts.remove(loadout_index);
+        let loadouts = &mut categories[to_category].loadouts;
+        let new_index = new_index.min(loadouts.len());
+        loadouts.insert(new_index, loadout);
+
+        Ok(format!("Moved loadout `{}` to index {} of category `{}`", loadout_id, new_index, category_id))
+    })
+}
+
+#[tauri::command]
+pub fn add_weapon_slot(
+    state: tauri::State<'_, AppState>,
+    loadout_id: String,
+    name: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let (category_index, loadout_index) = loadout_position(categories, &loadout_id)?;
+        let slots = &mut categories[category_index].loadouts[loadout_index].slots;
+        let name = check_name("Weapon slot", &name, slots.iter().map(|slot| slot.name.as_str()))?;
+
+        slots.push(WeaponSlot {
+            name: name.clone(),
+            weapons: Vec::new(),
+            selected: 0,
+            attachments: HashMap::new(),
+        });
+
+        Ok(format!("Added weapon slot `{}` to loadout `{}`", name, loadout_id))
+    })
+}
+#[tauri::command]
+pub fn remove_weapon_slot(
+    state: tauri::State<'_, AppState>,
+    loadout_id: String,
+    slot_index: usize
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let (category_index, loadout_index) = loadout_position(categories, &loadout_id)?;
+        let slots = &mut categories[category_index].loadouts[loadout_index].slots;
+        if slot_index >= slots.len() {
+            return Err(format!("Weapon slot {} not found", slot_index));
+        }
+
+        let slot = slots.remove(slot_index);
+
+        Ok(format!("Removed weapon slot `{}` from loadout `{}`", slot.name, loadout_id))
+    })
+}
+/// Add a weapon to the end of a slot, the weapon must exist in the game
+#[tauri::command]
+pub fn add_loadout_weapon(
+    state: tauri::State<'_, AppState>,
+    loadout_id: String,
+    slot_index: usize,
+    weapon_id: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        if !game.weapons.as_ref().is_some_and(|weapons| weapons.contains_key(&weapon_id)) {
+            return Err(format!("Weapon ID `{}` not found in game `{}`", weapon_id, game.name));
+        }
+
+        let categories = categories_mut(game)?;
+        let (category_index, loadout_index) = loadout_position(categories, &loadout_id)?;
+        let slot = categories[category_index].loadouts[loadout_index].slots.get_mut(slot_index)
+            .ok_or(format!("Weapon slot {} not found", slot_index))?;
+        if slot.weapons.contains(&weapon_id) {
+            return Err(format!("Weapon `{}` is already in slot `{}`", weapon_id, slot.name));
+        }
+
+        slot.weapons.push(weapon_id.clone());
+
+        Ok(format!("Added weapon `{}` to slot `{}` of loadout `{}`", weapon_id, slot.name, loadout_id))
+    })
+}
+#[tauri::command]
+pub fn remove_loadout_weapon(
+    state: tauri::State<'_, AppState>,
+    loadout_id: String,
+    slot_index: usize,
+    weapon_id: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let categories = categories_mut(game)?;
+        let (category_index, loadout_index) = loadout_position(categories, &loadout_id)?;
+        let slot = categories[category_index].loadouts[loadout_index].slots.get_mut(slot_index)
+            .ok_or(format!("Weapon slot {} not found", slot_index))?;
+        if !slot.remove_weapon(&weapon_id) {
+            return Err(format!("Weapon `{}` is not in slot `{}`", weapon_id, slot.name));
+        }
+
+        Ok(format!("Removed weapon `{}` from slot `{}` of loadout `{}`", weapon_id, slot.name, loadout_id))
+    })
+}
+/// Select the attachments used with a weapon in a slot, replacing the current ones
+///
+/// An empty list removes all attachments.
+#[tauri::command]
+pub fn set_slot_attachments(
+    state: tauri::State<'_, AppState>,
+    loadout_id: String,
+    slot_index: usize,
+    weapon_id: String,
+    attachment_ids: Vec<String>
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        if let Some(missing) = attachment_ids.iter().find(|id| !game.attachments.contains_key(*id)) {
+            return Err(format!("Attachment `{}` not found in game `{}`", missing, game.name));
+        }
+
+        let categories = categories_mut(game)?;
+        let (category_index, loadout_index) = loadout_position(categories, &loadout_id)?;
+        let slot = categories[category_index].loadouts[loadout_index].slots.get_mut(slot_index)
+            .ok_or(format!("Weapon slot {} not found", slot_index))?;
+        if !slot.weapons.contains(&weapon_id) {
+            return Err(format!("Weapon `{}` is not in slot `{}`", weapon_id, slot.name));
+        }
+
+        let change = format!("Set {} attachment(s) for weapon `{}` in slot `{}` of loadout `{}`", attachment_ids.len(), weapon_id, slot.name, loadout_id);
+        if attachment_ids.is_empty() {
+            slot.attachments.remove(&weapon_id);
+        } else {
+            slot.attachments.insert(weapon_id, attachment_ids);
+        }
+
+        Ok(change)
+    })
+}
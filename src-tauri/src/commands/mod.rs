@@ -1,4 +1,8 @@
 pub mod config;
 pub mod selection;
+pub mod loadouts;
+pub mod weapons;
+pub mod attachments;
 pub mod app;
-pub mod state;
\ No newline at end of file
+pub mod state;
+pub mod stats;
\ No newline at end of file
@@ -1,117 +1,225 @@
-use crate::{save_data, types::{AppEvent, AppState, Game}};
+use crate::{
+    save_data,
+    selection::{enter_category, update_selection, Selection},
+    stance::{self, Stance},
+    types::{AppEvent, AppState, Game},
+};
 
-#[tauri::command]
-pub async fn change_game (
-    state: tauri::State<'_, AppState>,
+/// Switch to another game, returning to what was last selected in it or
+/// otherwise starting from its first category and loadout
+pub fn select_game (
+    state: &AppState,
     new_game_index: usize
 ) -> Result<usize, String> {
-    if new_game_index < state.games.read_arc().len() {
-        state.current_game_index.store(new_game_index, std::sync::atomic::Ordering::Relaxed);
-        println!("Changed game to index {}", new_game_index);
-        return Ok(new_game_index);
-    }
+    let saved = state.games.read_arc().get(new_game_index)
+        .and_then(|game| state.selection.saved(&game.name));
+
+    update_selection(state, |selection, games| {
+        if new_game_index >= games.len() {
+            return Err(format!("Invalid game index: {}", new_game_index));
+        }
+        if new_game_index == selection.game {
+            return Ok(selection);
+        }
+
+        // Entries that no longer exist fall back to the first ones
+        Ok(saved
+            .map(|ids| ids.resolve(games))
+            .filter(|restored| restored.game == new_game_index)
+            .unwrap_or(Selection {
+                game: new_game_index,
+                category: 0,
+                loadout: 0,
+                ..selection
+            }))
+    })?;
+    println!("Changed game to index {}", new_game_index);
 
-    Err(format!("Invalid game index: {}", new_game_index))
+    Ok(new_game_index)
 }
-#[tauri::command]
-pub async fn change_category (
-    state: tauri::State<'_, AppState>,
+/// Switch to another category of the current game, returning to the loadout and
+/// weapon last used in it
+pub fn select_category (
+    state: &AppState,
     new_category_index: usize
 ) -> Result<usize, String> {
-    let current_game_index = state.current_game_index.load(std::sync::atomic::Ordering::Relaxed);
-
-    if let Some(game) = state.games.read_arc().get(current_game_index) {
-        if new_category_index <     game.categories.as_ref().map(|c| c.len()).unwrap_or(0) {
-            state.current_category_index.store(new_category_index, std::sync::atomic::Ordering::Relaxed);
-            println!("Changed category to index {}", new_category_index);
-            return Ok(new_category_index);
+    enter_category(state, |selection, games| {
+        let category_count = games.get(selection.game)
+            .and_then(|game| game.categories.as_ref())
+            .map(|c| c.len())
+            .unwrap_or(0);
+        if new_category_index >= category_count {
+            return Err(format!("Invalid category index: {}", new_category_index));
         }
-    }
 
-    Err(format!("Invalid category index: {}", new_category_index))
+        Ok(new_category_index)
+    })?;
+    println!("Changed category to index {}", new_category_index);
+
+    Ok(new_category_index)
 }
-#[tauri::command]
-pub async fn change_loadout (
-    state: tauri::State<'_, AppState>,
+pub fn select_loadout (
+    state: &AppState,
     new_loadout_index: usize
 ) -> Result<usize, String> {
-    let current_game_index = state.current_game_index.load(std::sync::atomic::Ordering::Relaxed);
-    let current_category_index = state.current_category_index.load(std::sync::atomic::Ordering::Relaxed);
+    update_selection(state, |selection, games| {
+        let game = games.get(selection.game)
+            .ok_or(format!("Invalid loadout index: {}", new_loadout_index))?;
+        let loadout_count = game.categories
+            .as_ref().ok_or(format!("Game `{}` does not have data loaded.", selection.game))?
+            .get(selection.category)
+            .map(|category| category.loadouts.len())
+            .unwrap_or(0);
+        if new_loadout_index >= loadout_count {
+            return Err(format!("Invalid loadout index: {}", new_loadout_index));
+        }
 
-    if let Some(game) = state.games.read_arc().get(current_game_index) {
-        if let Some(category) = game.categories
-            .as_ref().ok_or(format!("Game `{}` does not have data loaded.", current_game_index))?
-            .get(current_category_index)
-        {
-            if new_loadout_index < category.loadouts.len() {
-                state.current_loadout_index.store(new_loadout_index, std::sync::atomic::Ordering::Relaxed);
-                println!("Changed loadout to index {}", new_loadout_index);
-                
-                // Emit event for loadout change
-                let _ = state.events_channel_sender.send(AppEvent::SwitchedLoadout {
-                    loadout_ind: new_loadout_index,
-                });
-                
-                return Ok(new_loadout_index);
-            }
+        Ok(Selection { loadout: new_loadout_index, ..selection })
+    })?;
+    println!("Changed loadout to index {}", new_loadout_index);
+
+    Ok(new_loadout_index)
+}
+/// Switch to the category with `category_id` in the current game, returning its index
+pub fn select_category_by_id (
+    state: &AppState,
+    category_id: &str
+) -> Result<usize, String> {
+    let selection = enter_category(state, |selection, games| {
+        games.get(selection.game)
+            .and_then(|game| game.categories.as_ref())
+            .and_then(|categories| categories.iter().position(|category| category.id == category_id))
+            .ok_or(format!("Category `{}` not found", category_id))
+    })?;
+    println!("Changed category to `{}` (index {})", category_id, selection.category);
+
+    Ok(selection.category)
+}
+/// Switch to the loadout with `loadout_id` in the current game, along with its category,
+/// returning its index within the category
+pub fn select_loadout_by_id (
+    state: &AppState,
+    loadout_id: &str
+) -> Result<usize, String> {
+    let selection = update_selection(state, |selection, games| {
+        let (category_index, loadout_index) = games.get(selection.game)
+            .and_then(|game| game.categories.as_ref())
+            .and_then(|categories| categories.iter().enumerate().find_map(|(category_index, category)| {
+                category.loadouts.iter()
+                    .position(|loadout| loadout.id == loadout_id)
+                    .map(|loadout_index| (category_index, loadout_index))
+            }))
+            .ok_or(format!("Loadout `{}` not found", loadout_id))?;
+
+        Ok(Selection {
+            category: category_index,
+            loadout: loadout_index,
+            ..selection
+        })
+    })?;
+    println!("Changed loadout to `{}` (index {})", loadout_id, selection.loadout);
+
+    Ok(selection.loadout)
+}
+/// Switch to another weapon slot of the current loadout
+pub fn select_weapon (
+    state: &AppState,
+    new_weapon_index: usize
+) -> Result<usize, String> {
+    update_selection(state, |selection, games| {
+        if new_weapon_index >= selection.slot_count(games) {
+            return Err(format!("Invalid weapon slot: {}", new_weapon_index));
         }
+
+        Ok(Selection { weapon: new_weapon_index, ..selection })
+    })?;
+
+    Ok(new_weapon_index)
+}
+pub fn set_engine_paused (
+    state: &AppState,
+    paused: bool
+) -> bool {
+    let was_paused = state.paused.swap(paused, std::sync::atomic::Ordering::SeqCst);
+    if was_paused != paused {
+        println!("Recoil engine {}", if paused { "paused" } else { "resumed" });
+        state.events.publish(AppEvent::PausedChanged { paused });
     }
 
-    Err(format!("Invalid loadout index: {}", new_loadout_index))
+    paused
 }
 #[tauri::command]
-pub async fn change_primary_weapon (
+pub async fn change_game (
     state: tauri::State<'_, AppState>,
-    new_primary_index: usize
-) -> Result<Vec<Game>, String> {
-    let current_game_index = state.current_game_index.load(std::sync::atomic::Ordering::Relaxed);
-    let current_category_index = state.current_category_index.load(std::sync::atomic::Ordering::Relaxed);
-    let current_loadout_index = state.current_loadout_index.load(std::sync::atomic::Ordering::Relaxed);
-
-    let mut games = state.games.write_arc();
-    if let Some(game) = games.get_mut(current_game_index) {
-        if let Some(category) = game.categories
-            .as_mut().ok_or("Game does not have data loaded.")?
-            .get_mut(current_category_index)
-        {
-            if let Some(loadout) = category.loadouts.get_mut(current_loadout_index) {
-                if new_primary_index < loadout.primaries.len() {
-                    loadout.selected_primary = new_primary_index;
-                    println!("Changed primary weapon to index {}", new_primary_index);
-                    
-                    // Save the updated data
-                    drop(games); // Release the lock before calling save_data
-                    save_data(&state).map_err(|e| format!("Failed to save data: {}", e))?;
-                    
-                    return Ok(state.games.read_arc().clone());
-                } else {
-                    return Err(format!("Primary weapon index {} out of bounds", new_primary_index));
-                }
-            }
-        }
-    }
-    
-    Err("Failed to change primary weapon".to_string())
+    new_game_index: usize
+) -> Result<usize, String> {
+    select_game(&state, new_game_index)
+}
+/// Returns the index of the category for the grid
+#[tauri::command]
+pub async fn change_category (
+    state: tauri::State<'_, AppState>,
+    category_id: String
+) -> Result<usize, String> {
+    select_category_by_id(&state, &category_id)
+}
+/// Returns the index of the loadout within its category for the grid
+#[tauri::command]
+pub async fn change_loadout (
+    state: tauri::State<'_, AppState>,
+    loadout_id: String
+) -> Result<usize, String> {
+    select_loadout_by_id(&state, &loadout_id)
 }
 #[tauri::command]
-pub async fn change_secondary_weapon (
+pub async fn set_paused (
     state: tauri::State<'_, AppState>,
-    new_secondary_index: usize
+    paused: bool
+) -> Result<bool, String> {
+    Ok(set_engine_paused(&state, paused))
+}
+#[tauri::command]
+pub fn get_paused (
+    state: tauri::State<'_, AppState>
+) -> bool {
+    state.paused.load(std::sync::atomic::Ordering::SeqCst)
+}
+#[tauri::command]
+pub fn get_stance (
+    state: tauri::State<'_, AppState>
+) -> Stance {
+    state.stance.get()
+}
+/// Override the stance tracked from the crouch and prone keys, e.g. when it got out of sync with the game
+#[tauri::command]
+pub fn set_stance (
+    state: tauri::State<'_, AppState>,
+    stance: Stance
+) -> Stance {
+    stance::set_stance(&state, stance);
+    stance
+}
+/// Choose which weapon of a slot in the current loadout is used
+#[tauri::command]
+pub async fn change_slot_weapon (
+    state: tauri::State<'_, AppState>,
+    slot_index: usize,
+    new_weapon_index: usize
 ) -> Result<Vec<Game>, String> {
-    let current_game_index = state.current_game_index.load(std::sync::atomic::Ordering::Relaxed);
-    let current_category_index = state.current_category_index.load(std::sync::atomic::Ordering::Relaxed);
-    let current_loadout_index = state.current_loadout_index.load(std::sync::atomic::Ordering::Relaxed);
+    let selection = state.selection.get();
 
     let mut games = state.games.write_arc();
-    if let Some(game) = games.get_mut(current_game_index) {
+    if let Some(game) = games.get_mut(selection.game) {
         if let Some(category) = game.categories
             .as_mut().ok_or("Game does not have data loaded.")?
-            .get_mut(current_category_index)
+            .get_mut(selection.category)
         {
-            if let Some(loadout) = category.loadouts.get_mut(current_loadout_index) {
-                if new_secondary_index < loadout.secondaries.len() {
-                    loadout.selected_secondary = new_secondary_index;
-                    println!("Changed secondary weapon to index {}", new_secondary_index);
+            if let Some(loadout) = category.loadouts.get_mut(selection.loadout) {
+                let slot = loadout.slots.get_mut(slot_index)
+                    .ok_or(format!("Weapon slot {} not found", slot_index))?;
+                if new_weapon_index < slot.weapons.len() {
+                    slot.selected = new_weapon_index;
+                    println!("Changed {} weapon to index {}", slot.name, new_weapon_index);
                     
                     // Save the updated data
                     drop(games); // Release the lock before calling save_data
@@ -119,11 +227,11 @@ pub async fn change_secondary_weapon (
                     
                     return Ok(state.games.read_arc().clone());
                 } else {
-                    return Err(format!("Secondary weapon index {} out of bounds", new_secondary_index));
+                    return Err(format!("{} weapon index {} out of bounds", slot.name, new_weapon_index));
                 }
             }
         }
     }
     
-    Err("Failed to change secondary weapon".to_string())
-}
\ No newline at end of file
+    Err("Failed to change weapon".to_string())
+}
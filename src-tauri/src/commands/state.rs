@@ -1,4 +1,4 @@
-use crate::{SERVER_BASE_URL, load_games, merge_game_configs, types::{AppEvent, AppState, Game, GlobalConfig, KeyStatus, KeyStatusResponse, LoadedGames}, winapi::get_hardware_identifier};
+use crate::{SERVER_BASE_URL, IntegrityReport, assign_ids, merge_game_configs, refresh_games, report_integrity, save_data, selection::revalidate_selection, types::{AppEvent, AppState, Game, GlobalConfig, KeyStatus, KeyStatusResponse}, winapi::get_hardware_identifier};
 
 #[tauri::command]
 pub fn get_games(state: tauri::State<'_, AppState>) -> Vec<Game> {
@@ -21,13 +21,26 @@ pub fn get_version(
 pub async fn load_games_wrapper (
     state: tauri::State<'_, AppState>
 ) -> Result<(), String> {
-    let LoadedGames {
-        game_data,
-    } = load_games((*state.config_dir_path).clone()).await?;
+    refresh_games(&state).await
+}
+/// Check every loaded game for data the engine can't use, repairing what
+/// can be safely repaired if `repair` is set
+#[tauri::command]
+pub fn check_game_data(
+    state: tauri::State<'_, AppState>,
+    repair: bool
+) -> Result<Vec<IntegrityReport>, String> {
+    let reports = report_integrity(&state, &mut state.games.write_arc(), repair);
 
-    *state.games.write_arc() = game_data;
+    if reports.iter().any(|report| !report.repairs.is_empty()) {
+        revalidate_selection(&state);
+        save_data(&state).map_err(|e| format!("Failed to save data: {}", e))?;
+        state.events.publish(AppEvent::UpdatedGames {
+            games: state.games.read_arc().clone(),
+        });
+    }
 
-    Ok(())
+    Ok(reports)
 }
 
 #[tauri::command]
@@ -86,6 +99,8 @@ pub async fn submit_game_key(
                 
                 // Use merge logic to preserve existing weapon configurations
                 merge_game_configs(game, config);
+                assign_ids(game);
+                report_integrity(&state, std::slice::from_mut(game), false);
                 
                 println!("Updated game '{}' with configuration from server (preserving local changes)", game_name);
             },
@@ -93,6 +108,7 @@ pub async fn submit_game_key(
                 game.key_status = Some(KeyStatus::Invalid { key: key.clone() });
                 game.categories = None;  // Clear categories for invalid keys
                 game.weapons = None;     // Clear weapons for invalid keys
+                game.attachments.clear(); // Clear attachments for invalid keys
                 println!("Key for game '{}' is invalid", game_name);
             },
             KeyStatusResponse::Expired { key, timestamp } => {
@@ -102,27 +118,37 @@ pub async fn submit_game_key(
                 });
                 game.categories = None;  // Clear categories for expired keys
                 game.weapons = None;     // Clear weapons for expired keys
+                game.attachments.clear(); // Clear attachments for expired keys
                 println!("Key for game '{}' is expired", game_name);
             },
             KeyStatusResponse::Banned { key } => {
                 game.key_status = Some(KeyStatus::Banned { key: key.clone() });
                 game.categories = None;  // Clear categories for banned keys
                 game.weapons = None;     // Clear weapons for banned keys
+                game.attachments.clear(); // Clear attachments for banned keys
                 println!("Key for game '{}' is banned", game_name);
             },
             KeyStatusResponse::HWIDMismatch { key } => {
                 game.key_status = Some(KeyStatus::HWIDMismatch { key: key.clone() });
                 game.categories = None;  // Clear categories for HWID mismatch
                 game.weapons = None;     // Clear weapons for HWID mismatch
+                game.attachments.clear(); // Clear attachments for HWID mismatch
                 println!("Key for game '{}' has HWID mismatch", game_name);
             },
         }
+
+        state.events.publish(AppEvent::LicenseStatusChanged {
+            game: game.name.clone(),
+            key_status: game.key_status.clone(),
+        });
     }
     
     let updated_games = games.clone();
+    drop(games);
+    revalidate_selection(&state);
     
     // Notify frontend of updated games
-    let _ = state.events_channel_sender.send(AppEvent::UpdatedGames {
+    state.events.publish(AppEvent::UpdatedGames {
         games: updated_games.clone(),
     });
     
@@ -0,0 +1,29 @@
+use std::collections::BTreeMap;
+
+use crate::{stats::{StatsScope, StatsSummary}, types::AppState};
+
+#[tauri::command]
+pub fn get_stats_summary (
+    state: tauri::State<'_, AppState>,
+    game_name: String,
+    scope: StatsScope
+) -> StatsSummary {
+    state.stats.lock().summary(&game_name, scope)
+}
+#[tauri::command]
+pub fn get_daily_stats (
+    state: tauri::State<'_, AppState>,
+    game_name: String
+) -> BTreeMap<String, StatsSummary> {
+    state.stats.lock().daily(&game_name)
+}
+#[tauri::command]
+pub fn reset_stats (
+    state: tauri::State<'_, AppState>,
+    game_name: Option<String>
+) -> Result<(), String> {
+    state.stats.lock().reset(game_name.as_deref())?;
+    println!("Reset stats for {}", game_name.as_deref().unwrap_or("all games"));
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use super::loadouts::edit_game;
+use crate::{
+    rename_weapon, resolved_weapon, weapon_usages, weapon_variants, WeaponUsage,
+    types::{AppState, Game, Weapon, WeaponEntry, WeaponVariant},
+    worker::RecoilCommand,
+};
+
+fn weapons_mut(
+    game: &mut Game
+) -> Result<&mut HashMap<String, WeaponEntry>, String> {
+    let name = game.name.clone();
+    game.weapons.as_mut()
+        .ok_or(format!("Game `{}` does not have data loaded", name))
+}
+/// Trim `weapon_id` and check it's neither empty nor already used
+fn check_weapon_id(
+    weapons: &HashMap<String, WeaponEntry>,
+    weapon_id: &str
+) -> Result<String, String> {
+    let weapon_id = weapon_id.trim();
+    if weapon_id.is_empty() {
+        return Err("Weapon ID can't be empty".to_string());
+    }
+    if weapons.contains_key(weapon_id) {
+        return Err(format!("Weapon ID `{}` already exists", weapon_id));
+    }
+
+    Ok(weapon_id.to_string())
+}
+/// Create a weapon of type `kind` (`SingleFire`, `SingleShot` or `FullAutoStandard`)
+/// with default values, to be tuned with `change_weapon_config`
+#[tauri::command]
+pub fn create_weapon(
+    state: tauri::State<'_, AppState>,
+    weapon_id: String,
+    kind: String,
+    name: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let weapons = weapons_mut(game)?;
+        let weapon_id = check_weapon_id(weapons, &weapon_id)?;
+
+        weapons.insert(weapon_id.clone(), Weapon::with_defaults(&kind, name)?.into());
+
+        Ok(format!("Created {} weapon `{}`", kind, weapon_id))
+    })
+}
+/// Create a variant of another weapon, which copies everything from it until
+/// fields are overridden with `change_weapon_config`
+#[tauri::command]
+pub fn create_weapon_variant(
+    state: tauri::State<'_, AppState>,
+    weapon_id: String,
+    base_weapon_id: String,
+    name: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let weapons = weapons_mut(game)?;
+        let weapon_id = check_weapon_id(weapons, &weapon_id)?;
+        if !weapons.contains_key(&base_weapon_id) {
+            return Err(format!("Weapon ID `{}` not found", base_weapon_id));
+        }
+
+        let mut variant = WeaponEntry::Variant(WeaponVariant {
+            base: base_weapon_id.clone(),
+            overrides: Default::default(),
+        });
+        variant.set_name(name);
+        weapons.insert(weapon_id.clone(), variant);
+
+        Ok(format!("Created weapon `{}` as a variant of `{}`", weapon_id, base_weapon_id))
+    })
+}
+/// Copy a weapon under a new ID, e.g. to try another tuning without losing the original
+#[tauri::command]
+pub fn duplicate_weapon(
+    state: tauri::State<'_, AppState>,
+    weapon_id: String,
+    new_weapon_id: String,
+    name: String
+) -> Result<Vec<Game>, String> {
+    edit_game(&state, |game| {
+        let weapons = weapons_mut(game)?;
+        let new_weapon_id = check_weapon_id(weapons, &new_weapon_id)?;
+
+        let mut copy = weapons.get(&weapon_id)
+            .ok_or(format!("Weapon ID `{}` not found", weapon_id))?
+            .clone();
+        copy.set_name(name);
+        weapons.insert(new_weapon_id.clone(), copy);
+
+        Ok(format!("Duplicated weapon `{}` as `{}`", weapon_id, new_weapon_id))
+    })
+}
+/// Delete a weapon, refusing while loadouts still use it unless `remove_references`
+/// is set, in which case it's removed from those loadouts as well
+///
+/// Weapons that are the base of variants can't be deleted before those variants.
+#[tauri::command]
+pub fn delete_weapon(
+    state: tauri::State<'_, AppState>,
+    weapon_id: String,
+    remove_references: bool
+) -> Result<Vec<Game>, String> {
+    let games = edit_game(&state, |game| {
+        let users: Vec<String> = weapon_usages(game).remove(&weapon_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|usage| format!("{}/{}", usage.category, usage.loadout))
+            .collect();
+        if !users.is_empty() && !remove_references {
+            return Err(format!("Weapon `{}` is still used by {} loadout slot(s): {}", weapon_id, users.len(), users.join(", ")));
+        }
+        let variants = weapon_variants(game, &weapon_id);
+        if !variants.is_empty() {
+            return Err(format!("Weapon `{}` is the base of {} variant(s): {}", weapon_id, variants.len(), variants.join(", ")));
+        }
+
+        weapons_mut(game)?.remove(&weapon_id)
+            .ok_or(format!("Weapon ID `{}` not found", weapon_id))?;
+        game.removed.weapons.insert(weapon_id.clone());
+
+        for slot in game.categories.iter_mut()
+            .flatten()
+            .flat_map(|category| category.loadouts.iter_mut())
+            .flat_map(|loadout| loadout.slots.iter_mut())
+        {
+            slot.remove_weapon(&weapon_id);
+        }
+
+        Ok(format!("Deleted weapon `{}`, removing it from {} loadout slot(s)", weapon_id, users.len()))
+    })?;
+
+    // The ID may be reused by a new weapon, which shouldn't inherit the trigger cap
+    state.recoil_worker.send(RecoilCommand::ClearTiming { weapon_id });
+
+    Ok(games)
+}
+/// Change a weapon's ID, updating every loadout and variant that uses it
+#[tauri::command]
+pub fn rename_weapon_id(
+    state: tauri::State<'_, AppState>,
+    weapon_id: String,
+    new_weapon_id: String
+) -> Result<Vec<Game>, String> {
+    let new_weapon_id = new_weapon_id.trim().to_string();
+    let games = edit_game(&state, |game| {
+        let updated = rename_weapon(game, &weapon_id, &new_weapon_id)?;
+
+        Ok(format!("Renamed weapon `{}` to `{}`, updating {} loadout slot(s)", weapon_id, new_weapon_id, updated))
+    })?;
+
+    state.recoil_worker.send(RecoilCommand::RenameTiming { weapon_id, new_weapon_id });
+
+    Ok(games)
+}
+/// Every loadout slot each weapon of the selected game is in, by weapon ID
+#[tauri::command]
+pub fn get_weapon_usages(
+    state: tauri::State<'_, AppState>
+) -> Result<HashMap<String, Vec<WeaponUsage>>, String> {
+    let game_index = state.selection.get().game;
+    let games = state.games.read_arc();
+    let game = games.get(game_index)
+        .ok_or(format!("Game index {} not found", game_index))?;
+
+    Ok(weapon_usages(game))
+}
+/// Every weapon of a game as stored, with variants as their base and overrides
+#[tauri::command]
+pub fn get_raw_weapons(
+    state: tauri::State<'_, AppState>,
+    game_name: String
+) -> Result<HashMap<String, WeaponEntry>, String> {
+    let games = state.games.read_arc();
+    let game = games.iter()
+        .find(|game| game.name == game_name)
+        .ok_or(format!("Game `{}` not found", game_name))?;
+
+    Ok(game.weapons.clone().unwrap_or_default())
+}
+/// Every weapon of a game as the engine fires it, with variants resolved
+///
+/// Variants that can't be resolved are left out, see `check_game_data` for why.
+#[tauri::command]
+pub fn get_resolved_weapons(
+    state: tauri::State<'_, AppState>,
+    game_name: String
+) -> Result<HashMap<String, Weapon>, String> {
+    let games = state.games.read_arc();
+    let game = games.iter()
+        .find(|game| game.name == game_name)
+        .ok_or(format!("Game `{}` not found", game_name))?;
+
+    let Some(weapons) = game.weapons.as_ref() else { return Ok(HashMap::new()); };
+    Ok(weapons.keys()
+        .filter_map(|weapon_id| resolved_weapon(weapons, weapon_id).ok().map(|weapon| (weapon_id.clone(), weapon)))
+        .collect())
+}
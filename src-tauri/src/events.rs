@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::sync::{Arc, atomic::{AtomicU64, Ordering}};
+
+use parking_lot::Mutex;
+use tokio::sync::{broadcast, Notify};
+
+use crate::types::AppEvent;
+
+/// Events buffered per subscriber before a slow subscriber starts missing events
+pub const EVENT_BUFFER_SIZE: usize = 256;
+
+struct Subscription {
+    name: String,
+    cancel: Arc<Notify>,
+}
+
+/// Broadcasts `AppEvent`s to any number of subscribers (UI, logging, stats, ...)
+///
+/// Each subscriber has its own bounded buffer, so publishing never blocks and
+/// nothing accumulates when nobody is listening.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<AppEvent>,
+    next_id: Arc<AtomicU64>,
+    subscriptions: Arc<Mutex<HashMap<u64, Subscription>>>,
+}
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER_SIZE);
+
+        Self {
+            sender,
+            next_id: Arc::new(AtomicU64::new(0)),
+            subscriptions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+    /// Send an event to every current subscriber
+    pub fn publish(&self, event: AppEvent) {
+        // An error only means there are no subscribers right now, which is fine
+        let _ = self.sender.send(event);
+    }
+    pub fn subscribe(&self, name: impl Into<String>) -> EventSubscriber {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let name = name.into();
+        let cancel = Arc::new(Notify::new());
+
+        self.subscriptions.lock().insert(id, Subscription {
+            name: name.clone(),
+            cancel: cancel.clone(),
+        });
+        println!("Event subscriber `{}` ({}) registered", name, id);
+
+        EventSubscriber {
+            id,
+            name,
+            receiver: self.sender.subscribe(),
+            cancel,
+            subscriptions: self.subscriptions.clone(),
+        }
+    }
+    /// Stop every subscriber registered under `name`, returning how many were stopped
+    pub fn unsubscribe(&self, name: &str) -> usize {
+        let mut subscriptions = self.subscriptions.lock();
+        let ids: Vec<u64> = subscriptions.iter()
+            .filter(|(_, subscription)| subscription.name == name)
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in &ids {
+            if let Some(subscription) = subscriptions.remove(id) {
+                subscription.cancel.notify_one();
+            }
+        }
+
+        ids.len()
+    }
+    pub fn subscriber_count(&self) -> usize {
+        self.subscriptions.lock().len()
+    }
+}
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct EventSubscriber {
+    id: u64,
+    name: String,
+    receiver: broadcast::Receiver<AppEvent>,
+    cancel: Arc<Notify>,
+    subscriptions: Arc<Mutex<HashMap<u64, Subscription>>>,
+}
+impl EventSubscriber {
+    /// Wait for the next event
+    ///
+    /// Returns `None` once this subscriber has been unsubscribed or the bus is gone.
+    /// If the subscriber fell behind, the missed events are skipped and logged.
+    pub async fn recv(&mut self) -> Option<AppEvent> {
+        loop {
+            tokio::select! {
+                _ = self.cancel.notified() => return None,
+                result = self.receiver.recv() => match result {
+                    Ok(event) => return Some(event),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        eprintln!("Event subscriber `{}` lagged behind, skipped {} events", self.name, missed);
+                    },
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+impl Drop for EventSubscriber {
+    fn drop(&mut self) {
+        self.subscriptions.lock().remove(&self.id);
+        println!("Event subscriber `{}` ({}) dropped", self.name, self.id);
+    }
+}
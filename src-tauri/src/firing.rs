@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::recoil::movement_scale;
+use crate::game_data::{apply_attachments, resolved_weapon};
+use crate::resolve_active_weapon;
+use crate::selection::Selection;
+use crate::types::{ActiveWeapon, AppState, EffectiveConfig, Game, Weapon};
+
+/// Serializes refreshes so an older resolution can never overwrite a newer one
+static REFRESH_LOCK: Mutex<()> = Mutex::new(());
+
+/// Everything the recoil worker and input listener need, resolved ahead of time
+///
+/// Replaced as a whole on every selection or config change, so readers never
+/// take the `games` or `global_config` locks.
+pub struct FiringContext {
+    /// Keybinds and mouse settings for the selected game
+    pub config: EffectiveConfig,
+    /// The selected weapon, or why nothing can be fired
+    pub weapon: Result<ResolvedWeapon, String>,
+}
+pub struct ResolvedWeapon {
+    /// Index of the selected weapon slot
+    pub weapon_ind: usize,
+    pub active: ActiveWeapon,
+    pub weapon: Weapon,
+    /// See `recoil::movement_scale`
+    pub scale: (f32, f32),
+}
+impl FiringContext {
+    /// A context with no weapon, used until games are loaded
+    pub fn empty(config: EffectiveConfig) -> Self {
+        Self {
+            config,
+            weapon: Err("Games are not loaded".to_string()),
+        }
+    }
+}
+
+fn resolve_weapon(
+    games: &[Game],
+    selection: Selection,
+    config: &EffectiveConfig,
+) -> Result<ResolvedWeapon, String> {
+    let active = resolve_active_weapon(games, selection)
+        .map_err(|e| format!("Error getting weapon ID: {}", e))?;
+
+    // Variants are resolved here, so the engine only ever sees complete weapons
+    let weapon = games.get(selection.game)
+        .and_then(|game| game.weapons.as_ref())
+        .ok_or(format!("Weapon not found: {}", active.weapon_id))
+        .and_then(|weapons| resolved_weapon(weapons, &active.weapon_id))?;
+    let weapon = apply_attachments(&games[selection.game], weapon, &active.attachments)?;
+
+    Ok(ResolvedWeapon {
+        weapon_ind: selection.weapon,
+        scale: movement_scale(config, &weapon),
+        active,
+        weapon,
+    })
+}
+/// Re-resolve the active firing context and publish it to `state.firing_context`
+///
+/// Must be called after anything that changes the selection, games or config,
+/// and never while holding the `games` or `global_config` lock.
+pub fn refresh_firing_context(
+    state: &AppState
+) {
+    let _guard = REFRESH_LOCK.lock();
+
+    // Resolve everything from one selection, it may change in the meantime
+    let context = {
+        let games = state.games.read_arc();
+        let selection = state.selection.get();
+        let config = state.global_config.read_arc().effective(games.get(selection.game));
+        let weapon = resolve_weapon(&games, selection, &config);
+
+        FiringContext { config, weapon }
+    };
+    state.firing_context.store(Arc::new(context));
+}
@@ -0,0 +1,767 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::recoil::{simulate, SimulatedMove};
+use crate::types::{Attachment, Category, Game, GlobalConfig, Weapon, WeaponEntry};
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ValidationIssue {
+    pub severity: IssueSeverity,
+    /// Where the issue is, e.g. `weapons.ak47` or `categories.Attack.loadouts.Ash`
+    pub location: String,
+    pub message: String,
+}
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            IssueSeverity::Error => "error",
+            IssueSeverity::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", severity, self.location, self.message)
+    }
+}
+
+/// Weapons and/or loadouts of a game, in a stable order so exports diff cleanly
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct GameDataExport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weapons: Option<BTreeMap<String, WeaponEntry>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<BTreeMap<String, Attachment>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<Category>>,
+}
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ImportSummary {
+    pub weapons_added: usize,
+    pub weapons_replaced: usize,
+    pub loadouts_added: usize,
+    pub loadouts_replaced: usize,
+}
+
+/// Read a game's `data.json`
+pub fn load_game_file(
+    path: &Path
+) -> Result<Game, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+/// Read a `config.json`, or the defaults when no path is given
+pub fn load_config_file(
+    path: Option<&Path>
+) -> Result<GlobalConfig, String> {
+    let Some(path) = path else { return Ok(GlobalConfig::default()); };
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+pub fn save_game_file(
+    path: &Path,
+    game: &Game
+) -> Result<(), String> {
+    std::fs::write(
+        path,
+        serde_json::to_string_pretty(game).map_err(|e| format!("Failed to serialize game data: {}", e))?
+    ).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Turn a name into an identifier, e.g. `Attack (Ranked)` into `attack-ranked`
+fn slugify(name: &str) -> String {
+    let slug = name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if slug.is_empty() { "unnamed".to_string() } else { slug }
+}
+/// Generate an identifier from `name` that isn't in `taken`, and reserve it
+fn unique_id(name: &str, taken: &mut HashSet<String>) -> String {
+    let base = slugify(name);
+    let mut id = base.clone();
+    let mut suffix = 2;
+    while taken.contains(&id) {
+        id = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    taken.insert(id.clone());
+
+    id
+}
+/// Give every category and loadout without an ID (e.g. from older data or the server) one
+/// derived from its name, returning how many were assigned
+///
+/// Category IDs are unique within the game, and so are loadout IDs across all categories.
+pub fn assign_ids(
+    game: &mut Game
+) -> usize {
+    let Some(categories) = game.categories.as_mut() else { return 0; };
+
+    let mut category_ids: HashSet<String> = categories.iter()
+        .map(|category| category.id.clone())
+        .filter(|id| !id.is_empty())
+        .collect();
+    let mut loadout_ids: HashSet<String> = categories.iter()
+        .flat_map(|category| category.loadouts.iter())
+        .map(|loadout| loadout.id.clone())
+        .filter(|id| !id.is_empty())
+        .collect();
+
+    let mut assigned = 0;
+    for category in categories.iter_mut() {
+        if category.id.is_empty() {
+            category.id = unique_id(&category.name, &mut category_ids);
+            assigned += 1;
+        }
+        for loadout in category.loadouts.iter_mut() {
+            if loadout.id.is_empty() {
+                loadout.id = unique_id(&loadout.name, &mut loadout_ids);
+                assigned += 1;
+            }
+        }
+    }
+
+    assigned
+}
+
+/// A loadout slot that holds a weapon
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WeaponUsage {
+    pub category_id: String,
+    pub category: String,
+    pub loadout_id: String,
+    pub loadout: String,
+    pub slot: String,
+}
+/// Reverse index of a game's loadouts, listing every slot each weapon ID is in
+///
+/// Weapons that no loadout uses have no entry, and IDs that loadouts use
+/// without a matching weapon are included.
+pub fn weapon_usages(
+    game: &Game
+) -> HashMap<String, Vec<WeaponUsage>> {
+    let mut usages: HashMap<String, Vec<WeaponUsage>> = HashMap::new();
+    for category in game.categories.iter().flatten() {
+        for loadout in &category.loadouts {
+            for slot in &loadout.slots {
+                for weapon_id in &slot.weapons {
+                    usages.entry(weapon_id.clone()).or_default().push(WeaponUsage {
+                        category_id: category.id.clone(),
+                        category: category.name.clone(),
+                        loadout_id: loadout.id.clone(),
+                        loadout: loadout.name.clone(),
+                        slot: slot.name.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    usages
+}
+/// Resolve a weapon, applying the overrides of variants on top of their base weapons
+pub fn resolved_weapon(
+    weapons: &HashMap<String, WeaponEntry>,
+    weapon_id: &str,
+) -> Result<Weapon, String> {
+    // Walk down to the complete weapon, collecting overrides on the way
+    let mut overrides = Vec::new();
+    let mut visited = HashSet::new();
+    let mut id = weapon_id;
+    let base = loop {
+        if !visited.insert(id) {
+            return Err(format!("Weapon `{}` is based on itself through `{}`", weapon_id, id));
+        }
+        let entry = weapons.get(id)
+            .ok_or_else(|| match id == weapon_id {
+                true => format!("Weapon `{}` not found", id),
+                false => format!("Base weapon `{}` of `{}` not found", id, weapon_id),
+            })?;
+        match entry {
+            WeaponEntry::Weapon(weapon) => break weapon,
+            WeaponEntry::Variant(variant) => {
+                overrides.push((id, &variant.overrides));
+                id = &variant.base;
+            },
+        }
+    };
+    if overrides.is_empty() {
+        return Ok(base.clone());
+    }
+
+    let mut value = serde_json::to_value(base)
+        .map_err(|e| format!("Failed to serialize weapon `{}`: {}", id, e))?;
+    let config = value.get_mut("config")
+        .and_then(|config| config.as_object_mut())
+        .ok_or(format!("Weapon `{}` has no config", id))?;
+
+    // Closest to the base first, so each variant's own overrides win
+    for (variant_id, fields) in overrides.into_iter().rev() {
+        for (field, field_value) in fields {
+            if !config.contains_key(field) {
+                return Err(format!("Weapon `{}` overrides unknown field `{}`", variant_id, field));
+            }
+            config.insert(field.clone(), field_value.clone());
+        }
+    }
+
+    serde_json::from_value(value)
+        .map_err(|e| format!("Weapon `{}` has an invalid override: {}", weapon_id, e))
+}
+/// Apply the combined modifiers of the attachments with `attachment_ids` to a weapon
+pub fn apply_attachments(
+    game: &Game,
+    mut weapon: Weapon,
+    attachment_ids: &[String],
+) -> Result<Weapon, String> {
+    if attachment_ids.is_empty() {
+        return Ok(weapon);
+    }
+
+    let (mut horizontal, mut vertical, mut dx_offset, mut dy_offset) = (1.0, 1.0, 0.0, 0.0);
+    for attachment_id in attachment_ids {
+        let attachment = game.attachments.get(attachment_id)
+            .ok_or(format!("Attachment `{}` not found", attachment_id))?;
+        horizontal *= attachment.horizontal_multiplier;
+        vertical *= attachment.vertical_multiplier;
+        dx_offset += attachment.dx_offset;
+        dy_offset += attachment.dy_offset;
+    }
+
+    let (dx, dy) = weapon.offsets_mut();
+    *dx = *dx * horizontal + dx_offset;
+    *dy = *dy * vertical + dy_offset;
+
+    Ok(weapon)
+}
+/// IDs of the variants based directly on `weapon_id`, sorted
+pub fn weapon_variants(
+    game: &Game,
+    weapon_id: &str,
+) -> Vec<String> {
+    let mut variants: Vec<String> = game.weapons.iter()
+        .flatten()
+        .filter(|(_, entry)| entry.base() == Some(weapon_id))
+        .map(|(id, _)| id.clone())
+        .collect();
+    variants.sort();
+
+    variants
+}
+/// Change a weapon's ID and every loadout slot and variant referencing it,
+/// returning how many references were updated
+///
+/// The old ID is recorded in `Game::removed`, so merging the server config doesn't add it back.
+pub fn rename_weapon(
+    game: &mut Game,
+    weapon_id: &str,
+    new_weapon_id: &str,
+) -> Result<usize, String> {
+    let weapons = game.weapons.as_mut()
+        .ok_or(format!("Game `{}` does not have data loaded", game.name))?;
+    if new_weapon_id.is_empty() {
+        return Err("Weapon ID can't be empty".to_string());
+    }
+    if weapons.contains_key(new_weapon_id) {
+        return Err(format!("Weapon ID `{}` already exists", new_weapon_id));
+    }
+    let weapon = weapons.remove(weapon_id)
+        .ok_or(format!("Weapon ID `{}` not found", weapon_id))?;
+    weapons.insert(new_weapon_id.to_string(), weapon);
+    // The server still sends the weapon under its old ID
+    game.removed.weapons.insert(weapon_id.to_string());
+
+    let mut updated = 0;
+    for entry in weapons.values_mut() {
+        if let WeaponEntry::Variant(variant) = entry {
+            if variant.base == weapon_id {
+                variant.base = new_weapon_id.to_string();
+                updated += 1;
+            }
+        }
+    }
+    for slot in game.categories.iter_mut()
+        .flatten()
+        .flat_map(|category| category.loadouts.iter_mut())
+        .flat_map(|loadout| loadout.slots.iter_mut())
+    {
+        for id in slot.weapons.iter_mut().filter(|id| *id == weapon_id) {
+            *id = new_weapon_id.to_string();
+            updated += 1;
+        }
+        if let Some(attachments) = slot.attachments.remove(weapon_id) {
+            slot.attachments.insert(new_weapon_id.to_string(), attachments);
+        }
+    }
+
+    Ok(updated)
+}
+
+fn check_number(issues: &mut Vec<ValidationIssue>, location: &str, field: &str, value: f32) {
+    if !value.is_finite() {
+        issues.push(ValidationIssue {
+            severity: IssueSeverity::Error,
+            location: location.to_string(),
+            message: format!("`{}` is not a finite number", field),
+        });
+    }
+}
+/// Check a game's weapons and loadouts for values and references the engine can't use
+pub fn validate_game(
+    game: &Game
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut error = |location: String, message: String| issues.push(ValidationIssue {
+        severity: IssueSeverity::Error,
+        location,
+        message,
+    });
+
+    let empty_weapons = Default::default();
+    let weapons = game.weapons.as_ref().unwrap_or(&empty_weapons);
+    let mut referenced = HashSet::new();
+
+    let mut category_names = HashSet::new();
+    let mut category_ids = HashSet::new();
+    let mut loadout_ids = HashSet::new();
+    let mut missing_ids = Vec::new();
+    let mut empty = Vec::new();
+    for category in game.categories.iter().flatten() {
+        let category_location = format!("categories.{}", category.name);
+        if !category_names.insert(&category.name) {
+            error(category_location.clone(), "Duplicate category name".to_string());
+        }
+        if category.id.is_empty() {
+            missing_ids.push(category_location.clone());
+        } else if !category_ids.insert(&category.id) {
+            error(category_location.clone(), format!("Duplicate category ID `{}`", category.id));
+        }
+        if category.loadouts.is_empty() {
+            empty.push((category_location.clone(), "Category has no loadouts"));
+        }
+
+        let mut loadout_names = HashSet::new();
+        for loadout in &category.loadouts {
+            let location = format!("{}.loadouts.{}", category_location, loadout.name);
+            if !loadout_names.insert(&loadout.name) {
+                error(location.clone(), "Duplicate loadout name".to_string());
+            }
+            if loadout.id.is_empty() {
+                missing_ids.push(location.clone());
+            } else if !loadout_ids.insert(&loadout.id) {
+                error(location.clone(), format!("Duplicate loadout ID `{}`", loadout.id));
+            }
+
+            for slot in &loadout.slots {
+                for weapon_id in &slot.weapons {
+                    referenced.insert(weapon_id.as_str());
+                    if !weapons.contains_key(weapon_id) {
+                        error(location.clone(), format!("Unknown weapon `{}` in slot `{}`", weapon_id, slot.name));
+                    }
+                }
+                if !slot.weapons.is_empty() && slot.selected >= slot.weapons.len() {
+                    error(location.clone(), format!("Selected index {} is out of range for slot `{}` ({} weapons)", slot.selected, slot.name, slot.weapons.len()));
+                }
+                let mut attached: Vec<(&String, &Vec<String>)> = slot.attachments.iter().collect();
+                attached.sort();
+                for (weapon_id, attachment_ids) in attached {
+                    if !slot.weapons.contains(weapon_id) {
+                        error(location.clone(), format!("Attachments for weapon `{}` which isn't in slot `{}`", weapon_id, slot.name));
+                    }
+                    for attachment_id in attachment_ids.iter().filter(|id| !game.attachments.contains_key(*id)) {
+                        error(location.clone(), format!("Unknown attachment `{}` for weapon `{}` in slot `{}`", attachment_id, weapon_id, slot.name));
+                    }
+                }
+            }
+            if loadout.slots.iter().all(|slot| slot.weapons.is_empty()) {
+                empty.push((location, "Loadout has no weapons"));
+            }
+        }
+    }
+
+    for (location, message) in empty {
+        issues.push(ValidationIssue {
+            severity: IssueSeverity::Warning,
+            location,
+            message: message.to_string(),
+        });
+    }
+    for location in missing_ids {
+        issues.push(ValidationIssue {
+            severity: IssueSeverity::Warning,
+            location,
+            message: "No ID, one will be generated from the name on load".to_string(),
+        });
+    }
+
+    let mut attachment_ids: Vec<&String> = game.attachments.keys().collect();
+    attachment_ids.sort();
+    for attachment_id in attachment_ids {
+        let location = format!("attachments.{}", attachment_id);
+        let attachment = &game.attachments[attachment_id];
+        check_number(&mut issues, &location, "horizontal_multiplier", attachment.horizontal_multiplier);
+        check_number(&mut issues, &location, "vertical_multiplier", attachment.vertical_multiplier);
+        check_number(&mut issues, &location, "dx_offset", attachment.dx_offset);
+        check_number(&mut issues, &location, "dy_offset", attachment.dy_offset);
+    }
+
+    // Weapons only used as the base of variants are still in use
+    for entry in weapons.values() {
+        if let Some(base) = entry.base() {
+            referenced.insert(base);
+        }
+    }
+
+    let mut weapon_ids: Vec<&String> = weapons.keys().collect();
+    weapon_ids.sort();
+    for weapon_id in weapon_ids {
+        let location = format!("weapons.{}", weapon_id);
+        let weapon = match resolved_weapon(weapons, weapon_id) {
+            Ok(weapon) => weapon,
+            Err(e) => {
+                issues.push(ValidationIssue {
+                    severity: IssueSeverity::Error,
+                    location,
+                    message: e,
+                });
+                continue;
+            }
+        };
+        match &weapon {
+            Weapon::FullAutoStandard(config) => {
+                // Disabled weapons are never fired, so they can be left to fix later
+                if config.rpm == 0 {
+                    issues.push(ValidationIssue {
+                        severity: if config.enabled { IssueSeverity::Error } else { IssueSeverity::Warning },
+                        location: location.clone(),
+                        message: "`rpm` must be greater than 0".to_string(),
+                    });
+                }
+                check_number(&mut issues, &location, "first_shot_scale", config.first_shot_scale);
+                check_number(&mut issues, &location, "exponential_factor", config.exponential_factor);
+                check_number(&mut issues, &location, "dx", config.dx);
+                check_number(&mut issues, &location, "dy", config.dy);
+            },
+            Weapon::SingleFire(config) => {
+                check_number(&mut issues, &location, "dx", config.dx);
+                check_number(&mut issues, &location, "dy", config.dy);
+            },
+            Weapon::SingleShot(config) => {
+                check_number(&mut issues, &location, "dx", config.dx);
+                check_number(&mut issues, &location, "dy", config.dy);
+            },
+        }
+
+        if !referenced.contains(weapon_id.as_str()) {
+            issues.push(ValidationIssue {
+                severity: IssueSeverity::Warning,
+                location,
+                message: "Weapon is not used by any loadout".to_string(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// A change `repair_game` made to fix an issue
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Repair {
+    pub location: String,
+    pub message: String,
+}
+impl std::fmt::Display for Repair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "repaired: {}: {}", self.location, self.message)
+    }
+}
+/// Result of checking a game with `check_game`
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct IntegrityReport {
+    pub game: String,
+    /// Issues left after any repairs
+    pub issues: Vec<ValidationIssue>,
+    pub repairs: Vec<Repair>,
+}
+impl IntegrityReport {
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|issue| issue.severity == IssueSeverity::Error)
+    }
+}
+/// `name`, or `name (2)`, `name (3)`, ... if it's in `taken`, reserving the result
+fn unique_name(name: &str, taken: &mut HashSet<String>) -> String {
+    let mut unique = name.to_string();
+    let mut suffix = 2;
+    while taken.contains(&unique) {
+        unique = format!("{} ({})", name, suffix);
+        suffix += 1;
+    }
+    taken.insert(unique.clone());
+
+    unique
+}
+/// Fix the errors `validate_game` reports where there's a safe fix, returning what was changed
+///
+/// Unknown weapons are removed from loadouts, out of range selections reset, duplicate
+/// names numbered, duplicate IDs regenerated and weapons without an `rpm` disabled.
+/// Nothing is ever deleted besides dangling references.
+pub fn repair_game(
+    game: &mut Game
+) -> Vec<Repair> {
+    let mut repairs = Vec::new();
+    let mut repaired = |location: String, message: String| repairs.push(Repair { location, message });
+
+    let weapon_ids: HashSet<String> = game.weapons.iter()
+        .flat_map(|weapons| weapons.keys().cloned())
+        .collect();
+    let game_attachments: HashSet<String> = game.attachments.keys().cloned().collect();
+
+    let mut category_names = HashSet::new();
+    let mut category_ids = HashSet::new();
+    let mut loadout_ids = HashSet::new();
+    for category in game.categories.iter_mut().flatten() {
+        let name = unique_name(&category.name, &mut category_names);
+        if name != category.name {
+            repaired(format!("categories.{}", category.name), format!("Renamed duplicate category to `{}`", name));
+            category.name = name;
+        }
+        let category_location = format!("categories.{}", category.name);
+        if !category.id.is_empty() && !category_ids.insert(category.id.clone()) {
+            repaired(category_location.clone(), format!("Replaced duplicate category ID `{}`", category.id));
+            category.id.clear();
+        }
+
+        let mut loadout_names = HashSet::new();
+        for loadout in category.loadouts.iter_mut() {
+            let name = unique_name(&loadout.name, &mut loadout_names);
+            if name != loadout.name {
+                repaired(format!("{}.loadouts.{}", category_location, loadout.name), format!("Renamed duplicate loadout to `{}`", name));
+                loadout.name = name;
+            }
+            let location = format!("{}.loadouts.{}", category_location, loadout.name);
+            if !loadout.id.is_empty() && !loadout_ids.insert(loadout.id.clone()) {
+                repaired(location.clone(), format!("Replaced duplicate loadout ID `{}`", loadout.id));
+                loadout.id.clear();
+            }
+
+            for slot in loadout.slots.iter_mut() {
+                let selected = slot.selected_weapon().cloned();
+                let before = slot.weapons.len();
+                slot.weapons.retain(|weapon_id| weapon_ids.contains(weapon_id));
+                if slot.weapons.len() != before {
+                    repaired(location.clone(), format!("Removed {} unknown weapon(s) from slot `{}`", before - slot.weapons.len(), slot.name));
+                }
+
+                let weapons = &slot.weapons;
+                let before = slot.attachments.len();
+                slot.attachments.retain(|weapon_id, _| weapons.contains(weapon_id));
+                if slot.attachments.len() != before {
+                    repaired(location.clone(), format!("Removed attachments of {} weapon(s) no longer in slot `{}`", before - slot.attachments.len(), slot.name));
+                }
+                for (weapon_id, attachment_ids) in slot.attachments.iter_mut() {
+                    let before = attachment_ids.len();
+                    attachment_ids.retain(|attachment_id| game_attachments.contains(attachment_id));
+                    if attachment_ids.len() != before {
+                        repaired(location.clone(), format!("Removed {} unknown attachment(s) from weapon `{}` in slot `{}`", before - attachment_ids.len(), weapon_id, slot.name));
+                    }
+                }
+
+                // Keep the same weapon selected if it's still there
+                let index = selected.and_then(|selected| slot.weapons.iter().position(|id| *id == selected));
+                match index {
+                    Some(index) => slot.selected = index,
+                    None if slot.selected != 0 && slot.selected >= slot.weapons.len() => {
+                        repaired(location.clone(), format!("Reset out of range selection in slot `{}`", slot.name));
+                        slot.selected = 0;
+                    },
+                    None => {},
+                }
+            }
+        }
+    }
+
+    if let Some(weapons) = game.weapons.as_mut() {
+        let mut disabled: Vec<String> = weapons.keys()
+            .filter(|weapon_id| matches!(
+                resolved_weapon(weapons, weapon_id),
+                Ok(Weapon::FullAutoStandard(config)) if config.rpm == 0 && config.enabled
+            ))
+            .cloned()
+            .collect();
+        disabled.sort();
+        for weapon_id in disabled {
+            match weapons.get_mut(&weapon_id) {
+                Some(WeaponEntry::Weapon(Weapon::FullAutoStandard(config))) => config.enabled = false,
+                Some(WeaponEntry::Variant(variant)) => {
+                    variant.overrides.insert("enabled".to_string(), serde_json::Value::Bool(false));
+                },
+                _ => continue,
+            }
+            repaired(format!("weapons.{}", weapon_id), "Disabled weapon with an `rpm` of 0".to_string());
+        }
+    }
+
+    assign_ids(game);
+
+    repairs
+}
+/// Validate a game, first repairing what can be repaired if `repair` is set
+pub fn check_game(
+    game: &mut Game,
+    repair: bool,
+) -> IntegrityReport {
+    let repairs = if repair { repair_game(game) } else { Vec::new() };
+
+    IntegrityReport {
+        game: game.name.clone(),
+        issues: validate_game(game),
+        repairs,
+    }
+}
+
+pub fn export_game_data(
+    game: &Game,
+    include_weapons: bool,
+    include_loadouts: bool,
+) -> GameDataExport {
+    GameDataExport {
+        weapons: game.weapons.as_ref()
+            .filter(|_| include_weapons)
+            .map(|weapons| weapons.iter().map(|(id, weapon)| (id.clone(), weapon.clone())).collect()),
+        attachments: Some(&game.attachments)
+            .filter(|attachments| include_weapons && !attachments.is_empty())
+            .map(|attachments| attachments.iter().map(|(id, attachment)| (id.clone(), attachment.clone())).collect()),
+        categories: game.categories.clone()
+            .filter(|_| include_loadouts),
+    }
+}
+/// Merge exported weapons and loadouts into a game, replacing weapons with the same ID and
+/// categories/loadouts with the same name
+///
+/// Replaced categories and loadouts keep their ID unless the import sets one.
+pub fn import_game_data(
+    game: &mut Game,
+    data: GameDataExport,
+) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+
+    if let Some(imported_weapons) = data.weapons {
+        let weapons = game.weapons.get_or_insert_with(Default::default);
+        for (weapon_id, weapon) in imported_weapons {
+            match weapons.insert(weapon_id, weapon) {
+                Some(_) => summary.weapons_replaced += 1,
+                None => summary.weapons_added += 1,
+            }
+        }
+    }
+
+    // Replaced like weapons, but not counted separately
+    if let Some(imported_attachments) = data.attachments {
+        game.attachments.extend(imported_attachments);
+    }
+
+    if let Some(imported_categories) = data.categories {
+        let categories = game.categories.get_or_insert_with(Vec::new);
+        for imported_category in imported_categories {
+            let Some(category) = categories.iter_mut().find(|c| c.name == imported_category.name) else {
+                summary.loadouts_added += imported_category.loadouts.len();
+                categories.push(imported_category);
+                continue;
+            };
+
+            for imported_loadout in imported_category.loadouts {
+                match category.loadouts.iter_mut().find(|l| l.name == imported_loadout.name) {
+                    Some(loadout) => {
+                        let id = std::mem::take(&mut loadout.id);
+                        *loadout = imported_loadout;
+                        if loadout.id.is_empty() {
+                            loadout.id = id;
+                        }
+                        summary.loadouts_replaced += 1;
+                    },
+                    None => {
+                        category.loadouts.push(imported_loadout);
+                        summary.loadouts_added += 1;
+                    }
+                }
+            }
+        }
+    }
+    assign_ids(game);
+
+    summary
+}
+/// Multiply the `dx`/`dy` of every weapon, or only of `weapon_ids` if any are given,
+/// returning how many weapons were changed
+///
+/// Variants only have the `dx`/`dy` they override scaled, the rest follows their base.
+pub fn scale_weapons(
+    game: &mut Game,
+    dx_factor: f32,
+    dy_factor: f32,
+    weapon_ids: &[String],
+) -> Result<usize, String> {
+    let weapons = game.weapons.as_mut()
+        .ok_or(format!("Game `{}` has no weapons", game.name))?;
+
+    if let Some(missing) = weapon_ids.iter().find(|id| !weapons.contains_key(*id)) {
+        return Err(format!("Weapon `{}` not found", missing));
+    }
+
+    let mut scaled = 0;
+    for (weapon_id, weapon) in weapons.iter_mut() {
+        if !weapon_ids.is_empty() && !weapon_ids.contains(weapon_id) {
+            continue;
+        }
+
+        let (dx, dy) = match weapon {
+            WeaponEntry::Weapon(weapon) => weapon.offsets_mut(),
+            WeaponEntry::Variant(variant) => {
+                let mut changed = false;
+                for (field, factor) in [("dx", dx_factor), ("dy", dy_factor)] {
+                    if let Some(value) = variant.overrides.get_mut(field) {
+                        let scaled_value = value.as_f64()
+                            .ok_or(format!("Weapon `{}` overrides `{}` with a non-number", weapon_id, field))?
+                            * factor as f64;
+                        *value = serde_json::json!(scaled_value);
+                        changed = true;
+                    }
+                }
+                if changed {
+                    scaled += 1;
+                }
+                continue;
+            },
+        };
+        *dx *= dx_factor;
+        *dy *= dy_factor;
+        scaled += 1;
+    }
+
+    Ok(scaled)
+}
+/// The mouse moves the engine would send for a weapon with the given config
+pub fn simulate_weapon(
+    game: &Game,
+    config: &GlobalConfig,
+    weapon_id: &str,
+    shots: u32,
+) -> Result<Vec<SimulatedMove>, String> {
+    let weapons = game.weapons.as_ref()
+        .ok_or(format!("Game `{}` has no weapons", game.name))?;
+    let weapon = resolved_weapon(weapons, weapon_id)?;
+
+    Ok(simulate(&config.effective(Some(game)), &weapon, shots))
+}
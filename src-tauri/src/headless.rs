@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use crate::commands::selection::select_game;
+use crate::types::{AppState, KeyStatus};
+use crate::{init_state, refresh_games, save_data, start_engine};
+
+/// Tauri's identifier, used to find the same config directory as the desktop app
+const APP_IDENTIFIER: &str = "com.hiibolt.clc-jpd";
+
+pub struct HeadlessOptions {
+    /// Directory containing `config.json`, the `games` directory and the `.key` files
+    pub config_dir: PathBuf,
+    /// Name of the game to select on startup, otherwise the last selected or first licensed game
+    pub game: Option<String>,
+}
+
+/// The directory the desktop app keeps its config in (`%APPDATA%\<identifier>`)
+pub fn default_config_dir() -> Option<PathBuf> {
+    std::env::var_os("APPDATA")
+        .map(|app_data| PathBuf::from(app_data).join(APP_IDENTIFIER))
+}
+
+/// Run the input listener and recoil engine without a webview
+///
+/// The engine is controlled with the usual hotkeys and, if enabled in
+/// `config.json`, the local API. Blocks until Ctrl+C is pressed.
+pub fn run_headless(
+    options: HeadlessOptions
+) -> Result<(), String> {
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| format!("Failed to start async runtime: {}", e))?;
+
+    let result = runtime.block_on(async {
+        println!("Using config directory: {}", options.config_dir.display());
+        let state = init_state(options.config_dir)?;
+
+        // Nothing is usable without server validation, so a failed fetch is fatal here
+        refresh_games(&state).await?;
+        let game_index = pick_game(&state, options.game.as_deref())?;
+        select_game(&state, game_index)?;
+
+        start_engine(&state);
+        println!("Engine running headless, press Ctrl+C to exit");
+
+        tokio::signal::ctrl_c().await
+            .map_err(|e| format!("Failed to listen for Ctrl+C: {}", e))?;
+
+        println!("Shutting down...");
+        save_data(&state)
+    });
+
+    // The input listener blocks its worker thread, so don't wait on it
+    runtime.shutdown_background();
+
+    result
+}
+fn pick_game(
+    state: &AppState,
+    requested: Option<&str>,
+) -> Result<usize, String> {
+    let games = state.games.read_arc();
+
+    for game in games.iter() {
+        let status = match &game.key_status {
+            Some(KeyStatus::Valid { .. }) => "valid",
+            Some(KeyStatus::Invalid { .. }) => "invalid",
+            Some(KeyStatus::Expired { .. }) => "expired",
+            Some(KeyStatus::Banned { .. }) => "banned",
+            Some(KeyStatus::HWIDMismatch { .. }) => "HWID mismatch",
+            None => "no key",
+        };
+        println!("Game `{}`: {}", game.name, status);
+    }
+
+    let is_licensed = |index: &usize| matches!(games[*index].key_status, Some(KeyStatus::Valid { .. }));
+    match requested {
+        Some(name) => {
+            let index = games.iter()
+                .position(|game| game.name == name)
+                .ok_or(format!("Game `{}` not found", name))?;
+            if !is_licensed(&index) {
+                return Err(format!("Game `{}` does not have a valid key", name));
+            }
+
+            Ok(index)
+        },
+        // Prefer the game restored from the last session
+        None => Some(state.selection.get().game)
+            .filter(|index| *index < games.len() && is_licensed(index))
+            .or_else(|| (0..games.len()).find(is_licensed))
+            .ok_or("No game has a valid key, add one with the desktop app first".to_string()),
+    }
+}
@@ -2,70 +2,119 @@ mod winapi;
 mod recoil;
 mod types;
 mod commands;
+mod sensitivity;
+mod events;
+mod stats;
+mod local_api;
+mod headless;
+mod game_data;
+mod worker;
+mod scheduler;
+mod motion;
+mod firing;
+mod selection;
+mod stance;
+
+pub use headless::{default_config_dir, run_headless, HeadlessOptions};
+pub use game_data::{
+    assign_ids, check_game, export_game_data, import_game_data, load_config_file, load_game_file, rename_weapon,
+    repair_game, resolved_weapon, save_game_file, scale_weapons, simulate_weapon, validate_game, weapon_usages,
+    weapon_variants, GameDataExport, ImportSummary, IntegrityReport, IssueSeverity, Repair, ValidationIssue,
+    WeaponUsage,
+};
 
+use arc_swap::ArcSwap;
 use parking_lot::{Mutex, RwLock};
 use tauri::{App, Builder, Manager};
 use anyhow::{anyhow, Result};
 
-use std::{path::PathBuf, sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc}, collections::HashMap};
+use std::{path::PathBuf, sync::{atomic::AtomicBool, Arc}, collections::{HashMap, HashSet}};
 
 use crate::{
     commands::{
         config::*,
         selection::*,
+        loadouts::*,
+        weapons::*,
+        attachments::*,
         app::*,
-        state::*
+        state::*,
+        stats::*
     }, 
+    events::EventBus,
+    firing::{refresh_firing_context, FiringContext},
+    selection::{enter_category, revalidate_selection, update_selection, SavedSelections, Selection, SelectionState},
+    stats::{StatsRecorder, StatsTracker},
     types::{KeyStatus, KeyStatusResponse, LoadedGames}, 
-    winapi::{get_hardware_identifier, main_recoil}
+    winapi::{get_hardware_identifier, main_recoil},
+    worker::RecoilWorkerHandle,
 };
-use crate::types::{AppEvent, AppState, Game, GlobalConfig};
+use crate::types::{ActiveWeapon, AppEvent, AppState, EffectiveConfig, Game, GlobalConfig};
 
 pub const SERVER_BASE_URL: &'static str = "http://45.146.252.244:4777";
 
-fn get_weapon_id (
+fn get_active_weapon (
     state: &AppState,
-) -> Result<String> {
+) -> Result<ActiveWeapon> {
     let games = state.games.read_arc();
-    let current_game_index = state.current_game_index.load(Ordering::SeqCst);
-    let current_game = &games.get(current_game_index)
-        .ok_or(anyhow!("Game index {} not found", current_game_index))?;
+    resolve_active_weapon(&games, state.selection.get())
+}
+/// Find the weapon a selection points at
+fn resolve_active_weapon (
+    games: &[Game],
+    selection: Selection,
+) -> Result<ActiveWeapon> {
+    let current_game = &games.get(selection.game)
+        .ok_or(anyhow!("Game index {} not found", selection.game))?;
 
     // Get the current category
-    let current_category_index = state.current_category_index.load(Ordering::SeqCst);
     let current_category = &current_game.categories
         .as_ref().ok_or(anyhow!("Game `{}` does not have categories loaded", current_game.name))?
-        .get(current_category_index)
-        .ok_or(anyhow!("Category index {} not found in game `{}`", current_category_index, current_game.name))?;
+        .get(selection.category)
+        .ok_or(anyhow!("Category index {} not found in game `{}`", selection.category, current_game.name))?;
 
     // Get the current loadout
-    let current_loadout_index = state.current_loadout_index.load(Ordering::SeqCst);
-    let current_loadout = &current_category.loadouts.get(current_loadout_index)
-        .ok_or(anyhow!("Loadout index {} not found in category `{}` in game `{}`", current_loadout_index, current_category.name, current_game.name))?;
-
-    // Get the current weapon index (0 = primary, 1 = secondary)
-    let weapon_ind = if current_loadout.secondaries.is_empty() {
-        0
-    } else {
-        state.current_weapon_index.load(Ordering::SeqCst)
-    };
-    let weapon_id = if weapon_ind == 0 {
-        // Primary weapon
-        if current_loadout.primaries.is_empty() {
-            return Err(anyhow!("No primary weapons in loadout `{}` in category `{}` in game `{}`", current_loadout.name, current_category.name, current_game.name));
-        }
-        current_loadout.primaries.get(current_loadout.selected_primary)
-            .ok_or(anyhow!("Selected primary index `{}` not found in loadout `{}` in category `{}` in game `{}`", current_loadout.selected_primary, current_loadout.name, current_category.name, current_game.name))?
-    } else {
-        // Secondary weapon
-        if current_loadout.secondaries.is_empty() {
-            return Err(anyhow!("No secondary weapons in loadout `{}` in category `{}` in game `{}`", current_loadout.name, current_category.name, current_game.name));
-        }
-        current_loadout.secondaries.get(current_loadout.selected_secondary)
-            .ok_or(anyhow!("Selected secondary index `{}` not found in loadout `{}` in category `{}` in game `{}`", current_loadout.selected_secondary, current_loadout.name, current_category.name, current_game.name))?
-    };
+    let current_loadout = &current_category.loadouts.get(selection.loadout)
+        .ok_or(anyhow!("Loadout index {} not found in category `{}` in game `{}`", selection.loadout, current_category.name, current_game.name))?;
+
+    // Get the selected slot, falling back to the first one with weapons if it's empty
+    let slot = current_loadout.slots.get(selection.weapon)
+        .filter(|slot| !slot.weapons.is_empty())
+        .or_else(|| current_loadout.slots.iter().find(|slot| !slot.weapons.is_empty()))
+        .ok_or(anyhow!("No weapons in loadout `{}` in category `{}` in game `{}`", current_loadout.name, current_category.name, current_game.name))?;
+    let weapon_id = slot.selected_weapon()
+        .ok_or(anyhow!("Selected {} index `{}` not found in loadout `{}` in category `{}` in game `{}`", slot.name, slot.selected, current_loadout.name, current_category.name, current_game.name))?;
+
+    Ok(ActiveWeapon {
+        game: current_game.name.clone(),
+        category: current_category.name.clone(),
+        category_id: current_category.id.clone(),
+        loadout: current_loadout.name.clone(),
+        loadout_id: current_loadout.id.clone(),
+        weapon_id: weapon_id.clone(),
+        attachments: slot.attachments.get(weapon_id).cloned().unwrap_or_default(),
+    })
+}
+fn get_weapon_id (
+    state: &AppState,
+) -> Result<String> {
+    get_active_weapon(state).map(|active_weapon| active_weapon.weapon_id)
+}
+/// Log an engine failure and surface it to every event subscriber
+pub fn report_engine_error (
+    state: &AppState,
+    message: String,
+) {
+    eprintln!("{}", message);
+    state.events.publish(AppEvent::EngineError { message });
+}
+/// Resolve the keybinds and mouse settings for the currently selected game
+pub fn get_effective_config (
+    state: &AppState,
+) -> EffectiveConfig {
+    let games = state.games.read_arc();
 
-    Ok(weapon_id.clone())
+    state.global_config.read_arc().effective(games.get(state.selection.get().game))
 }
 fn load_local_games(games_dir_path: &PathBuf) -> Result<HashMap<String, Game>, String> {
     use std::collections::HashMap;
@@ -114,30 +163,63 @@ fn load_local_games(games_dir_path: &PathBuf) -> Result<HashMap<String, Game>, S
     
     Ok(local_games)
 }
+/// Add server categories, loadouts, weapons and attachments the local game doesn't have yet
+///
+/// Categories and loadouts are matched by ID, so renamed or moved ones aren't duplicated,
+/// and the ones in `Game::removed` stay deleted.
 pub fn merge_game_configs(local_game: &mut Game, remote_game: &Game) {
+    // Game-level metadata always follows the server
+    if remote_game.sensitivity_formula.is_some() {
+        local_game.sensitivity_formula = remote_game.sensitivity_formula;
+    }
+    if remote_game.reference_dpi.is_some() {
+        local_game.reference_dpi = remote_game.reference_dpi;
+    }
+    if remote_game.reference_sensitivity.is_some() {
+        local_game.reference_sensitivity = remote_game.reference_sensitivity;
+    }
+
+    // The server doesn't send IDs, generate the same ones the local copy got from these names.
+    // Local data from before IDs existed needs them too before it can be matched.
+    let mut remote_game = remote_game.clone();
+    assign_ids(&mut remote_game);
+    assign_ids(local_game);
+    let removed = &local_game.removed;
+
     // Only update if remote has valid data
-    if let Some(remote_categories) = &remote_game.categories {
+    if let Some(remote_categories) = remote_game.categories.take() {
         if let Some(local_categories) = &mut local_game.categories {
+            // Loadouts can be moved between categories, so look for them in all of them
+            let local_loadout_ids: HashSet<String> = local_categories.iter()
+                .flat_map(|category| category.loadouts.iter())
+                .map(|loadout| loadout.id.clone())
+                .collect();
+
             // Merge categories
-            for remote_category in remote_categories {
+            for mut remote_category in remote_categories {
+                if removed.categories.contains(&remote_category.id) {
+                    continue;
+                }
+                remote_category.loadouts.retain(|loadout| {
+                    !removed.loadouts.contains(&loadout.id) && !local_loadout_ids.contains(&loadout.id)
+                });
+
                 if let Some(local_category) = local_categories.iter_mut()
-                    .find(|c| c.name == remote_category.name) {
+                    .find(|c| c.id == remote_category.id) {
                     // Merge loadouts within the category
-                    for remote_loadout in &remote_category.loadouts {
-                        if !local_category.loadouts.iter().any(|l| l.name == remote_loadout.name) {
-                            println!("Adding new loadout '{}' to category '{}'", remote_loadout.name, remote_category.name);
-                            local_category.loadouts.push(remote_loadout.clone());
-                        }
+                    for remote_loadout in remote_category.loadouts {
+                        println!("Adding new loadout '{}' to category '{}'", remote_loadout.name, local_category.name);
+                        local_category.loadouts.push(remote_loadout);
                     }
                 } else {
                     // Add new category entirely
                     println!("Adding new category '{}'", remote_category.name);
-                    local_categories.push(remote_category.clone());
+                    local_categories.push(remote_category);
                 }
             }
         } else {
             // No local categories, use remote ones
-            local_game.categories = remote_game.categories.clone();
+            local_game.categories = Some(remote_categories);
         }
     }
     
@@ -145,7 +227,7 @@ pub fn merge_game_configs(local_game: &mut Game, remote_game: &Game) {
         if let Some(local_weapons) = &mut local_game.weapons {
             // Add new weapons, but don't overwrite existing ones
             for (weapon_id, remote_weapon) in remote_weapons {
-                if !local_weapons.contains_key(weapon_id) {
+                if !local_weapons.contains_key(weapon_id) && !removed.weapons.contains(weapon_id) {
                     println!("Adding new weapon '{}'", weapon_id);
                     local_weapons.insert(weapon_id.clone(), remote_weapon.clone());
                 }
@@ -155,6 +237,13 @@ pub fn merge_game_configs(local_game: &mut Game, remote_game: &Game) {
             local_game.weapons = remote_game.weapons.clone();
         }
     }
+
+    for (attachment_id, remote_attachment) in &remote_game.attachments {
+        if !local_game.attachments.contains_key(attachment_id) && !removed.attachments.contains(attachment_id) {
+            println!("Adding new attachment '{}'", attachment_id);
+            local_game.attachments.insert(attachment_id.clone(), remote_attachment.clone());
+        }
+    }
 }
 async fn load_games (
     config_dir: PathBuf
@@ -176,14 +265,10 @@ async fn load_games (
                 Err(e) => {
                     eprintln!("Failed to parse remote games list, using basic game info only: {}", e);
                     // If we can't get remote data, only provide basic game info (no local configs)
-                    let basic_games: Vec<Game> = local_games.keys().map(|name| Game {
-                        name: name.clone(),
-                        key: None,
-                        key_status: None,
-                        categories: None,
-                        weapons: None,
-                    }).collect();
-                    return Ok(LoadedGames { game_data: basic_games });
+                    let basic_games: Vec<Game> = local_games.keys()
+                        .map(|name| Game::without_config(name.clone(), None, None))
+                        .collect();
+                    return Ok(LoadedGames { game_data: basic_games, ids_assigned: 0 });
                 }
             }
         },
@@ -208,26 +293,14 @@ async fn load_games (
                 Err(e) => {
                     eprintln!("Failed to read key file for game `{}`: {}", game_id, e);
                     // No key file readable - only provide basic game info
-                    games_ret.push(Game {
-                        name: game_id.clone(),
-                        key: None,
-                        key_status: None,
-                        categories: None,
-                        weapons: None,
-                    });
+                    games_ret.push(Game::without_config(game_id.clone(), None, None));
                     continue;
                 }
             }
         } else {
             println!("No key file found for game `{}`", game_id);
             // No key file - only provide basic game info
-            games_ret.push(Game {
-                name: game_id.clone(),
-                key: None,
-                key_status: None,
-                categories: None,
-                weapons: None,
-            });
+            games_ret.push(Game::without_config(game_id.clone(), None, None));
             continue;
         };
 
@@ -268,7 +341,7 @@ async fn load_games (
                     games_ret.push(local_game);
                 } else {
                     // Use remote config directly
-                    let mut full_game = config.clone();
+                    let mut full_game = (**config).clone();
                     full_game.key = Some(key.clone());
                     full_game.key_status = Some(KeyStatus::Valid { 
                         key: key,
@@ -282,55 +355,47 @@ async fn load_games (
                 // Remove from local_games to prevent duplicate in final loop
                 local_games.remove(&game_id);
                 // HWID mismatch - only provide basic game info (no local config)
-                games_ret.push(Game {
-                    name: game_id.clone(),
-                    key: Some(key.clone()),
-                    key_status: Some(KeyStatus::HWIDMismatch { key: key.clone() }),
-                    categories: None,
-                    weapons: None,
-                });
+                games_ret.push(Game::without_config(
+                    game_id.clone(),
+                    Some(key.clone()),
+                    Some(KeyStatus::HWIDMismatch { key: key.clone() }),
+                ));
             },
             KeyStatusResponse::Invalid { key } => {
                 println!("Key for game `{}` is invalid", game_id);
                 // Remove from local_games to prevent duplicate in final loop
                 local_games.remove(&game_id);
                 // Invalid key - only provide basic game info (no local config)
-                games_ret.push(Game {
-                    name: game_id.clone(),
-                    key: Some(key.clone()),
-                    key_status: Some(KeyStatus::Invalid { key: key.clone() }),
-                    categories: None,
-                    weapons: None,
-                });
+                games_ret.push(Game::without_config(
+                    game_id.clone(),
+                    Some(key.clone()),
+                    Some(KeyStatus::Invalid { key: key.clone() }),
+                ));
             },
             KeyStatusResponse::Expired { key, timestamp } => {
                 println!("Key for game `{}` is expired", game_id);
                 // Remove from local_games to prevent duplicate in final loop
                 local_games.remove(&game_id);
                 // Expired key - only provide basic game info (no local config)
-                games_ret.push(Game {
-                    name: game_id.clone(),
-                    key: Some(key.clone()),
-                    key_status: Some(KeyStatus::Expired { 
+                games_ret.push(Game::without_config(
+                    game_id.clone(),
+                    Some(key.clone()),
+                    Some(KeyStatus::Expired { 
                         key: key.clone(), 
                         timestamp: *timestamp 
                     }),
-                    categories: None,
-                    weapons: None,
-                });
+                ));
             },
             KeyStatusResponse::Banned { key } => {
                 println!("Key for game `{}` is banned", game_id);
                 // Remove from local_games to prevent duplicate in final loop
                 local_games.remove(&game_id);
                 // Banned key - only provide basic game info (no local config)
-                games_ret.push(Game {
-                    name: game_id.clone(),
-                    key: Some(key.clone()),
-                    key_status: Some(KeyStatus::Banned { key: key.clone() }),
-                    categories: None,
-                    weapons: None,
-                });
+                games_ret.push(Game::without_config(
+                    game_id.clone(),
+                    Some(key.clone()),
+                    Some(KeyStatus::Banned { key: key.clone() }),
+                ));
             },
         }
     }
@@ -338,19 +403,25 @@ async fn load_games (
     // Add any remaining local games that weren't in the remote list (basic info only)
     for (game_name, local_game) in local_games {
         println!("Adding local-only game: {} (basic info only)", game_name);
-        games_ret.push(Game {
-            name: game_name,
-            key: local_game.key,
-            key_status: local_game.key_status,
-            categories: None,  // Never load local configs without server validation
-            weapons: None,     // Never load local configs without server validation
-        });
+        // Never load local configs without server validation
+        games_ret.push(Game::without_config(game_name, local_game.key, local_game.key_status));
+    }
+
+    // Migrate data from before categories and loadouts had IDs, and IDs for new server entries
+    let mut ids_assigned = 0;
+    for game in games_ret.iter_mut() {
+        let assigned = assign_ids(game);
+        if assigned > 0 {
+            println!("Generated {} category/loadout IDs for game `{}`", assigned, game.name);
+            ids_assigned += assigned;
+        }
     }
 
     println!("Loaded {} games", games_ret.len());
 
     Ok(LoadedGames {
         game_data: games_ret,
+        ids_assigned,
     })
 }
 fn load_config (
@@ -401,22 +472,24 @@ fn save_data(
         ).map_err(|e| format!("Failed to write game data for {}: {}", game.name, e))?;
     }
 
+    // Everything that changes games or config saves, so this keeps the engine up to date
+    refresh_firing_context(state);
+    state.events.publish(AppEvent::Saved);
+
     Ok(())
 }
-async fn setup(
-    app: &mut App
-) -> AppState {
-    // Note: We no longer automatically check for updates here
-    // Updates will be checked manually via the check_for_updates command
-
-    // Get the config directory path
-    let config_dir_path = Arc::new(app.path().app_config_dir()
-        .expect("Failed to get resource directory"));
+/// Load the config from `config_dir` and build the shared application state
+///
+/// Games are not loaded yet, see `refresh_games`
+fn init_state(
+    config_dir_path: PathBuf
+) -> Result<AppState, String> {
+    let config_dir_path = Arc::new(config_dir_path);
 
     // If the config directory path does not exist, create it
     if !config_dir_path.exists() {
         std::fs::create_dir_all(&*config_dir_path)
-            .expect("Failed to create config directory");
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
     }
 
     let config = match load_config(&config_dir_path) {
@@ -424,41 +497,151 @@ async fn setup(
         Err(e) => {
             // If loading data fails, create an `{config_dir}/logs` directory and log the error
             let log_dir = config_dir_path.join("logs");
-            std::fs::create_dir_all(&log_dir).expect("Failed to create logs directory");
+            std::fs::create_dir_all(&log_dir).map_err(|e| format!("Failed to create logs directory: {}", e))?;
             
             let log_file_path = log_dir.join("error.log");
-            std::fs::write(&log_file_path, &e).expect("Failed to write error log");
+            std::fs::write(&log_file_path, &e).map_err(|e| format!("Failed to write error log: {}", e))?;
             eprintln!("Failed to load data: {}. Error logged to {}", e, log_file_path.display());
             
-            panic!("Failed to load data: {}", e);
+            return Err(format!("Failed to load data: {}", e));
         }
     };
 
-    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    let stats = Arc::new(Mutex::new(StatsTracker::new(&config_dir_path)));
+    let stats_recorder = StatsRecorder::spawn(stats.clone());
+    let firing_context = FiringContext::empty(config.effective(None));
+    let selection = SelectionState::with_saved(SavedSelections::load(&config_dir_path), (*config_dir_path).clone());
     let state = AppState {
         games:           Arc::new(RwLock::new(vec!())),
         global_config:   Arc::new(RwLock::new(config)),
         config_dir_path,
+        events: EventBus::new(),
 
-        events_channel_sender:   Arc::new(event_tx),
-        events_channel_reciever: Arc::new(Mutex::new(event_rx)),
-
+        paused:                 Arc::new(AtomicBool::new(false)),
         left_hold_active:       Arc::new(AtomicBool::new(false)),
         right_hold_active:      Arc::new(AtomicBool::new(false)),
-        current_game_index:     Arc::new(AtomicUsize::new(0)),
-        current_category_index: Arc::new(AtomicUsize::new(0)),
-        current_loadout_index:  Arc::new(AtomicUsize::new(0)),
-        current_weapon_index:   Arc::new(AtomicUsize::new(0)),
+        selection,
+        stance:                 Default::default(),
         
         grid_layout_info:       Arc::new(RwLock::new(Default::default())),
-        last_shot_times:        Arc::new(RwLock::new(HashMap::new())),
+        firing_context:         Arc::new(ArcSwap::from_pointee(firing_context)),
+        recoil_worker:          RecoilWorkerHandle::new(),
+
+        stats,
+        stats_recorder,
+
+        local_api_task: Arc::new(Mutex::new(None)),
     };
+    refresh_firing_context(&state);
+
+    Ok(state)
+}
+/// Start the input listener, recoil engine and every background subscriber
+///
+/// Must be called from within a Tokio runtime
+fn start_engine(
+    state: &AppState
+) {
+    if let Err(e) = state.recoil_worker.start(state) {
+        eprintln!("Failed to start recoil worker: {}", e);
+    }
+
     let state_cloned = state.clone();
 
     tokio::spawn(async move {
         main_recoil(state_cloned);
     });
 
+    // Log every event, independently of whether a webview is listening
+    let mut log_subscriber = state.events.subscribe("log");
+    tokio::spawn(async move {
+        while let Some(event) = log_subscriber.recv().await {
+            println!("[event] {}", event.kind());
+        }
+    });
+
+    // Start the local control API if the user opted in
+    local_api::restart(state);
+}
+/// Validate every game key with the server and replace the loaded games
+async fn refresh_games(
+    state: &AppState
+) -> Result<(), String> {
+    let LoadedGames {
+        game_data,
+        ids_assigned,
+    } = load_games((*state.config_dir_path).clone()).await?;
+
+    // Report the validation result for every game with a key
+    for game in &game_data {
+        if game.key_status.is_some() {
+            state.events.publish(AppEvent::LicenseStatusChanged {
+                game: game.name.clone(),
+                key_status: game.key_status.clone(),
+            });
+        }
+    }
+
+    let mut game_data = game_data;
+    report_integrity(state, &mut game_data, false);
+
+    *state.games.write_arc() = game_data;
+    revalidate_selection(state);
+
+    // Keep generated IDs so they stay the same if names change later
+    if ids_assigned > 0 {
+        save_data(state)?;
+    }
+
+    Ok(())
+}
+/// Check every game with loaded data, logging errors and notifying the frontend of them
+///
+/// Returns a report for every checked game, including ones without issues.
+fn report_integrity(
+    state: &AppState,
+    games: &mut [Game],
+    repair: bool,
+) -> Vec<IntegrityReport> {
+    let reports: Vec<IntegrityReport> = games.iter_mut()
+        .filter(|game| game.categories.is_some() || game.weapons.is_some())
+        .map(|game| check_game(game, repair))
+        .collect();
+
+    for report in &reports {
+        for repair in &report.repairs {
+            println!("Game `{}`: {}", report.game, repair);
+        }
+        for error in report.errors() {
+            eprintln!("Game `{}`: {}", report.game, error);
+        }
+    }
+
+    let with_errors: Vec<IntegrityReport> = reports.iter()
+        .filter(|report| report.errors().next().is_some())
+        .cloned()
+        .collect();
+    if !with_errors.is_empty() {
+        state.events.publish(AppEvent::GameDataIssues { reports: with_errors });
+    }
+
+    reports
+}
+async fn setup(
+    app: &mut App
+) -> AppState {
+    // Note: We no longer automatically check for updates here
+    // Updates will be checked manually via the check_for_updates command
+
+    // Get the config directory path
+    let config_dir_path = app.path().app_config_dir()
+        .expect("Failed to get resource directory");
+
+    let state = init_state(config_dir_path)
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    start_engine(&state);
+
     state
 }
 pub fn run() {
@@ -479,22 +662,69 @@ pub fn run() {
             exit_app,
             restart_app,
             start_channel_reads,
+            stop_channel_reads,
+            get_recoil_worker_status,
+            start_recoil_worker,
+            stop_recoil_worker,
+            restart_recoil_worker,
 
             change_game,
             change_category,
             change_loadout,
-            change_primary_weapon,
-            change_secondary_weapon,
+            change_slot_weapon,
+            set_paused,
+            get_paused,
+            get_stance,
+            set_stance,
+
+            create_category,
+            rename_category,
+            delete_category,
+            duplicate_category,
+            move_category,
+            create_loadout,
+            rename_loadout,
+            delete_loadout,
+            duplicate_loadout,
+            move_loadout,
+            add_weapon_slot,
+            remove_weapon_slot,
+            add_loadout_weapon,
+            remove_loadout_weapon,
+            set_slot_attachments,
+            save_attachment,
+            delete_attachment,
+            create_weapon,
+            create_weapon_variant,
+            duplicate_weapon,
+            delete_weapon,
+            rename_weapon_id,
+            get_weapon_usages,
+            get_raw_weapons,
+            get_resolved_weapons,
+            check_game_data,
             
             change_horizontal_multiplier,
             change_vertical_multiplier,
             change_acog_horizontal_multiplier,
             change_acog_vertical_multiplier,
             change_scroll_wheel_weapon_swap,
+            change_mouse_dpi,
+            change_tick_rate,
+            change_local_api_settings,
+            regenerate_local_api_token,
             change_setting,
+            change_game_setting,
+            get_effective_config_for_game,
+            change_stance_modifier,
+            calculate_sensitivity,
             change_weapon_config,
             reset_config_from_server,
-            change_grid_layout
+            change_grid_layout,
+
+            get_stats_summary,
+            get_daily_stats,
+            reset_stats
 
         ])
         .setup(|app| {
@@ -511,100 +741,131 @@ pub fn run() {
 
 // Function for cycling through weapon types (used by scroll wheel)
 pub fn cycle_weapon_type(state: &AppState, scroll_up: bool) -> Result<usize, String> {
-    let current_game_index = state.current_game_index.load(std::sync::atomic::Ordering::Relaxed);
-    let current_category_index = state.current_category_index.load(std::sync::atomic::Ordering::Relaxed);
-    let current_loadout_index = state.current_loadout_index.load(std::sync::atomic::Ordering::Relaxed);
-    let current_weapon_index = state.current_weapon_index.load(std::sync::atomic::Ordering::Relaxed);
-
-    if let Some(game) = state.games.read_arc().get(current_game_index) {
-        if let Some(category) = game.categories
-            .as_ref().ok_or("Game does not have data loaded.")?
-            .get(current_category_index)
-        {
-            if let Some(loadout) = category.loadouts.get(current_loadout_index) {
-                // Check if both primary and secondary weapons are available
-                let has_primary = !loadout.primaries.is_empty();
-                let has_secondary = !loadout.secondaries.is_empty();
-                
-                if !has_primary && !has_secondary {
-                    return Err("No weapons available in current loadout".to_string());
-                }
-                
-                let next_weapon_index = if has_primary && has_secondary {
-                    // Both weapon types available, cycle between them
-                    if scroll_up {
-                        (current_weapon_index + 1) % 2
-                    } else {
-                        if current_weapon_index == 0 { 1 } else { 0 }
-                    }
-                } else if has_primary {
-                    // Only primary available
-                    0
-                } else {
-                    // Only secondary available  
-                    1
-                };
-                
-                // Only update if there's actually a change
-                if next_weapon_index != current_weapon_index {
-                    state.current_weapon_index.store(next_weapon_index, std::sync::atomic::Ordering::Relaxed);
-                    
-                    // Clear shot timing for trigger cap when switching weapons
-                    crate::recoil::clear_current_weapon_timing(state);
-                    
-                    println!("Cycled to weapon index {} (scroll {})", next_weapon_index, if scroll_up { "up" } else { "down" });
-                    
-                    // Send event to update the frontend
-                    if let Err(e) = state.events_channel_sender.send(AppEvent::SwitchedWeapon {
-                        weapon_ind: next_weapon_index,
-                    }) {
-                        eprintln!("Failed to send SwitchedWeapon event: {}", e);
-                    }
-                }
-                
-                return Ok(next_weapon_index);
-            }
+    let current = state.selection.get();
+
+    let next_weapon_index = {
+        let games = state.games.read_arc();
+        let loadout = games.get(current.game)
+            .and_then(|game| game.categories.as_ref())
+            .and_then(|categories| categories.get(current.category))
+            .and_then(|category| category.loadouts.get(current.loadout))
+            .ok_or("Invalid game, category, or loadout state")?;
+
+        // Only slots with weapons can be cycled to
+        let slot_count = loadout.slots.len();
+        let has_weapons = |slot: &usize| !loadout.slots[*slot].weapons.is_empty();
+        if !(0..slot_count).any(|slot| has_weapons(&slot)) {
+            return Err("No weapons available in current loadout".to_string());
         }
-    }
+        
+        // Step through the slots in scroll direction, wrapping around
+        (1..=slot_count)
+            .map(|step| if scroll_up {
+                (current.weapon + step) % slot_count
+            } else {
+                (current.weapon + slot_count * 2 - step) % slot_count
+            })
+            .find(has_weapons)
+            .unwrap_or(current.weapon)
+    };
+    
+    // Only update if there's actually a change
+    if next_weapon_index != current.weapon {
+        // Clear shot timing for trigger cap when switching weapons
+        crate::worker::clear_current_weapon_timing(state);
 
-    Err("Invalid game, category, or loadout state".to_string())
+        // Sends `SwitchedWeapon` to update the frontend
+        update_selection(state, |selection, _| Ok(Selection { weapon: next_weapon_index, ..selection }))?;
+        
+        println!("Cycled to weapon index {} (scroll {})", next_weapon_index, if scroll_up { "up" } else { "down" });
+    }
+    
+    Ok(next_weapon_index)
 }
 
 // Function for cycling through categories (used by INSERT key)
 fn cycle_category(state: &AppState) -> Result<usize, String> {
-    let current_game_index = state.current_game_index.load(std::sync::atomic::Ordering::Relaxed);
-    let current_category_index = state.current_category_index.load(std::sync::atomic::Ordering::Relaxed);
-
-    if let Some(game) = state.games.read_arc().get(current_game_index) {
-        let category_count = game.categories.as_ref().map(|c| c.len()).unwrap_or(0);
+    // Sends `SwitchedCategory` and `SwitchedLoadout` to update the frontend
+    let selection = enter_category(state, |selection, games| {
+        let category_count = games.get(selection.game)
+            .ok_or("No game selected")?
+            .categories.as_ref().map(|c| c.len()).unwrap_or(0);
         
         if category_count == 0 {
             return Err("No categories available".to_string());
         }
         
         // Cycle to next category, wrapping around to 0 if at the end
-        let next_category_index = (current_category_index + 1) % category_count;
-        
-        state.current_category_index.store(next_category_index, std::sync::atomic::Ordering::Relaxed);
-        state.current_loadout_index.store(0, std::sync::atomic::Ordering::Relaxed); // Reset loadout to first
-        
-        println!("Cycled to category index {} (from {})", next_category_index, current_category_index);
-        
-        // Send events to update the frontend
-        if let Err(e) = state.events_channel_sender.send(AppEvent::SwitchedCategory {
-            category_ind: next_category_index,
-        }) {
-            eprintln!("Failed to send SwitchedCategory event: {}", e);
-        }
-        
-        if let Err(e) = state.events_channel_sender.send(AppEvent::SwitchedLoadout {
-            loadout_ind: 0,
-        }) {
-            eprintln!("Failed to send SwitchedLoadout event: {}", e);
-        }
-        
-        return Ok(next_category_index);
+        Ok((selection.category + 1) % category_count)
+    })?;
+    
+    println!("Cycled to category index {}", selection.category);
+    
+    Ok(selection.category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_game() -> Game {
+        serde_json::from_value(serde_json::json!({
+            "name": "Test",
+            "key": null,
+            "key_status": null,
+            "categories": [
+                { "name": "Assault", "loadouts": [{ "name": "Ash", "icon_url": null }, { "name": "Thermite", "icon_url": null }] },
+                { "name": "Support", "loadouts": [{ "name": "Jager", "icon_url": null }] },
+            ],
+            "weapons": {},
+        })).unwrap()
     }
+    fn loadout_names(game: &Game) -> Vec<(String, Vec<String>)> {
+        game.categories.iter()
+            .flatten()
+            .map(|category| (category.id.clone(), category.loadouts.iter().map(|loadout| loadout.name.clone()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn merge_keeps_renamed_and_moved_items() {
+        let mut local = server_game();
+        assign_ids(&mut local);
+
+        let categories = local.categories.as_mut().unwrap();
+        categories[0].name = "Attackers".to_string();
+        categories[0].loadouts[0].name = "Ash (custom)".to_string();
+        let jager = categories[1].loadouts.remove(0);
+        categories[0].loadouts.push(jager);
+
+        let mut remote = server_game();
+        remote.categories.as_mut().unwrap()[0].loadouts.push(serde_json::from_value(serde_json::json!({
+            "name": "Sledge",
+            "icon_url": null,
+        })).unwrap());
+        merge_game_configs(&mut local, &remote);
+
+        assert_eq!(loadout_names(&local), vec![
+            ("assault".to_string(), vec!["Ash (custom)".to_string(), "Thermite".to_string(), "Jager".to_string(), "Sledge".to_string()]),
+            ("support".to_string(), vec![]),
+        ]);
+    }
+
+    #[test]
+    fn merge_does_not_restore_deleted_items() {
+        let mut local = server_game();
+        assign_ids(&mut local);
 
-    Err("No game selected".to_string())
-}
\ No newline at end of file
+        let categories = local.categories.as_mut().unwrap();
+        let thermite = categories[0].loadouts.remove(1);
+        let support = categories.remove(1);
+        local.removed.loadouts.insert(thermite.id);
+        local.removed.categories.insert(support.id);
+
+        merge_game_configs(&mut local, &server_game());
+
+        assert_eq!(loadout_names(&local), vec![
+            ("assault".to_string(), vec!["Ash".to_string()]),
+        ]);
+    }
+}
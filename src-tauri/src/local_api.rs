@@ -0,0 +1,297 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State, ws::{Message, WebSocket, WebSocketUpgrade}},
+    http::{StatusCode, header::AUTHORIZATION},
+    middleware::{self, Next},
+    response::Response,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::commands::selection::{select_category, select_category_by_id, select_game, select_loadout, select_loadout_by_id, set_engine_paused};
+use crate::stance::Stance;
+use crate::types::{AppState, EventEnvelope};
+use crate::worker::RecoilWorkerStatus;
+
+type ApiResult<T> = Result<Json<T>, (StatusCode, String)>;
+
+/// How long open requests get to finish when the API is stopped
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Deserialize)]
+struct IndexRequest {
+    index: usize,
+}
+/// Entries are picked by ID, the index is only used without one as it changes when entries are added or removed
+#[derive(Deserialize)]
+struct CategoryRequest {
+    #[serde(default)]
+    category_id: Option<String>,
+    #[serde(default)]
+    index: Option<usize>,
+}
+/// See `CategoryRequest`, the loadout's category is selected along with it
+#[derive(Deserialize)]
+struct LoadoutRequest {
+    #[serde(default)]
+    loadout_id: Option<String>,
+    #[serde(default)]
+    index: Option<usize>,
+}
+#[derive(Deserialize)]
+struct PausedRequest {
+    paused: bool,
+}
+#[derive(Serialize)]
+struct StatusResponse {
+    paused: bool,
+    game_index: usize,
+    category_index: usize,
+    loadout_index: usize,
+    weapon_index: usize,
+    game: Option<String>,
+    category_id: Option<String>,
+    loadout_id: Option<String>,
+    event_subscribers: usize,
+    recoil_worker: RecoilWorkerStatus,
+    stance: Stance,
+}
+
+/// Random hex token for authenticating local API clients, 32 bytes from the OS CSPRNG
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes)
+        .expect("Failed to generate local API token: OS randomness unavailable");
+
+    bytes.iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// A running local API instance
+pub struct LocalApiTask {
+    task: JoinHandle<()>,
+    /// Set to stop accepting, close idle connections and end event streams
+    shutdown: watch::Sender<bool>,
+}
+impl LocalApiTask {
+    /// Stop the instance and wait until its port is free, aborting it if requests don't finish in time
+    async fn stop(self) {
+        let _ = self.shutdown.send(true);
+
+        let mut task = self.task;
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut task).await.is_err() {
+            task.abort();
+            let _ = task.await;
+        }
+        println!("Stopped local API");
+    }
+}
+
+/// (Re)start the local API according to the current config, stopping any running instance
+///
+/// Clients of the previous instance are disconnected, so none keep using an old token.
+pub fn restart(
+    state: &AppState
+) {
+    let mut task = state.local_api_task.lock();
+    let previous = task.take();
+
+    let config = state.global_config.read_arc().local_api.clone();
+    if !config.enabled {
+        if let Some(previous) = previous {
+            tokio::spawn(previous.stop());
+        }
+        return;
+    }
+
+    let (shutdown, shutdown_signal) = watch::channel(false);
+    let state_cloned = state.clone();
+    *task = Some(LocalApiTask {
+        task: tokio::spawn(async move {
+            // The previous instance has to release the port first
+            if let Some(previous) = previous {
+                previous.stop().await;
+            }
+            if let Err(e) = serve(state_cloned, config.port, config.token, shutdown_signal).await {
+                eprintln!("Local API stopped: {}", e);
+            }
+        }),
+        shutdown,
+    });
+}
+async fn serve(
+    state: AppState,
+    port: u16,
+    token: String,
+    shutdown: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    let router = Router::new()
+        .route("/v1/status", get(get_status))
+        .route("/v1/game", post(post_game))
+        .route("/v1/category", post(post_category))
+        .route("/v1/loadout", post(post_loadout))
+        .route("/v1/paused", post(post_paused))
+        .route("/v1/events", get(get_events))
+        .layer(middleware::from_fn_with_state(token, require_token))
+        .layer(Extension(shutdown.clone()))
+        .with_state(state);
+
+    // Never listen on anything but loopback
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+    println!("Local API listening on http://127.0.0.1:{}", port);
+
+    let mut shutdown = shutdown;
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move {
+            // A dropped sender also means the instance is gone
+            let _ = shutdown.wait_for(|stop| *stop).await;
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Accepts the token as `Authorization: Bearer <token>` or, for WebSocket clients, `?token=<token>`
+async fn require_token(
+    State(token): State<String>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let authorized = {
+        let header_token = request.headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let query_token = request.uri()
+            .query()
+            .and_then(|query| query.split('&').find_map(|pair| pair.strip_prefix("token=")));
+
+        // Constant time, so the token can't be guessed byte by byte from response timings
+        header_token.or(query_token)
+            .is_some_and(|given| bool::from(given.as_bytes().ct_eq(token.as_bytes())))
+    };
+
+    if !authorized {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(next.run(request).await)
+}
+
+async fn get_status(
+    State(state): State<AppState>,
+) -> Json<StatusResponse> {
+    let (selection, ids) = {
+        let games = state.games.read_arc();
+        let selection = state.selection.get();
+        (selection, selection.ids(&games))
+    };
+    Json(StatusResponse {
+        paused: state.paused.load(Ordering::SeqCst),
+        game_index: selection.game,
+        category_index: selection.category,
+        loadout_index: selection.loadout,
+        weapon_index: selection.weapon,
+        game: ids.game,
+        category_id: ids.category,
+        loadout_id: ids.loadout,
+        event_subscribers: state.events.subscriber_count(),
+        recoil_worker: state.recoil_worker.status(),
+        stance: state.stance.get(),
+    })
+}
+async fn post_game(
+    State(state): State<AppState>,
+    Json(request): Json<IndexRequest>,
+) -> ApiResult<usize> {
+    select_game(&state, request.index)
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+async fn post_category(
+    State(state): State<AppState>,
+    Json(request): Json<CategoryRequest>,
+) -> ApiResult<usize> {
+    let result = match (request.category_id, request.index) {
+        (Some(category_id), _) => select_category_by_id(&state, &category_id),
+        (None, Some(index)) => select_category(&state, index),
+        (None, None) => Err("Either `category_id` or `index` is required".to_string()),
+    };
+
+    result
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+async fn post_loadout(
+    State(state): State<AppState>,
+    Json(request): Json<LoadoutRequest>,
+) -> ApiResult<usize> {
+    let result = match (request.loadout_id, request.index) {
+        (Some(loadout_id), _) => select_loadout_by_id(&state, &loadout_id),
+        (None, Some(index)) => select_loadout(&state, index),
+        (None, None) => Err("Either `loadout_id` or `index` is required".to_string()),
+    };
+
+    result
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+async fn post_paused(
+    State(state): State<AppState>,
+    Json(request): Json<PausedRequest>,
+) -> ApiResult<bool> {
+    Ok(Json(set_engine_paused(&state, request.paused)))
+}
+async fn get_events(
+    State(state): State<AppState>,
+    Extension(shutdown): Extension<watch::Receiver<bool>>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| forward_events(socket, state, shutdown))
+}
+/// Send every event to the client until it disconnects or the API instance is stopped
+async fn forward_events(
+    mut socket: WebSocket,
+    state: AppState,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut subscriber = state.events.subscribe("local-api");
+
+    loop {
+        tokio::select! {
+            // Upgraded connections aren't part of the server's graceful shutdown, and the
+            // signal is only ever set to stop
+            _ = shutdown.changed() => {
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            },
+            event = subscriber.recv() => {
+                let Some(event) = event else { break; };
+                let payload = match serde_json::to_string(&EventEnvelope::from(event)) {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        eprintln!("Failed to serialize event for local API: {}", e);
+                        continue;
+                    }
+                };
+
+                if socket.send(Message::Text(payload.into())).await.is_err() {
+                    break;
+                }
+            },
+            message = socket.recv() => {
+                // Clients only observe, anything but a close is ignored
+                match message {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::mem;
+use std::time::Duration;
+
+use winapi::um::winuser::*;
+
+use crate::scheduler::{JitterStats, Scheduler};
+
+pub const MIN_TICK_RATE: u32 = 125;
+pub const MAX_TICK_RATE: u32 = 1000;
+pub const DEFAULT_TICK_RATE: u32 = 500;
+
+/// Where the motion pipeline's ticks end up
+pub trait MotionSink {
+    /// Wait for the next tick, `interval` after the previous one
+    fn wait(&mut self, interval: Duration);
+    fn send(&mut self, dx: i32, dy: i32);
+}
+
+/// Sends moves to the OS, timed by a deadline scheduler
+pub struct LiveSink {
+    scheduler: Scheduler,
+}
+impl LiveSink {
+    pub fn start() -> Self {
+        Self {
+            scheduler: Scheduler::start(),
+        }
+    }
+}
+impl MotionSink for LiveSink {
+    fn wait(&mut self, interval: Duration) {
+        self.scheduler.wait(interval);
+    }
+    fn send(&mut self, dx: i32, dy: i32) {
+        unsafe {
+            let mut move_input = INPUT {
+                type_: INPUT_MOUSE,
+                u: mem::zeroed(),
+            };
+            *move_input.u.mi_mut() = MOUSEINPUT {
+                dx,
+                dy,
+                mouseData: 0,
+                dwFlags: MOUSEEVENTF_MOVE,
+                time: 0,
+                dwExtraInfo: 0,
+            };
+
+            SendInput(
+                1,
+                &mut move_input as *mut _,
+                std::mem::size_of::<INPUT>() as i32,
+            );
+        }
+    }
+}
+
+/// Turns fractional movements into integer mouse moves at a fixed tick rate
+///
+/// The fractional remainder carries over between movements, so nothing is
+/// lost to rounding over a spray, and ticks that round to nothing send nothing.
+pub struct MotionOutput<S: MotionSink> {
+    sink: S,
+    tick: Duration,
+    dx_remainder: f32,
+    dy_remainder: f32,
+}
+impl<S: MotionSink> MotionOutput<S> {
+    pub fn new(sink: S, tick_rate: u32) -> Self {
+        let tick_rate = tick_rate.clamp(MIN_TICK_RATE, MAX_TICK_RATE);
+
+        Self {
+            sink,
+            tick: Duration::from_secs(1) / tick_rate,
+            dx_remainder: 0.0,
+            dy_remainder: 0.0,
+        }
+    }
+    /// Spread a movement evenly over `duration`, one move per tick
+    ///
+    /// The tick count is rounded so the movement takes exactly `duration`.
+    pub fn move_over(&mut self, dx: f32, dy: f32, duration: Duration) {
+        let ticks = ((duration.as_secs_f64() / self.tick.as_secs_f64()).round() as u32).max(1);
+        let interval = duration / ticks;
+        let dx_step = dx / ticks as f32;
+        let dy_step = dy / ticks as f32;
+
+        for _ in 0..ticks {
+            self.sink.wait(interval);
+
+            self.dx_remainder += dx_step;
+            self.dy_remainder += dy_step;
+
+            let dx_send = self.dx_remainder.round() as i32;
+            let dy_send = self.dy_remainder.round() as i32;
+
+            // Keep whatever wasn't sent for the next tick
+            self.dx_remainder -= dx_send as f32;
+            self.dy_remainder -= dy_send as f32;
+
+            if dx_send != 0 || dy_send != 0 {
+                self.sink.send(dx_send, dy_send);
+            }
+        }
+    }
+    /// Wait without moving, keeping the remainder for the next movement
+    pub fn wait(&mut self, duration: Duration) {
+        self.sink.wait(duration);
+    }
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+}
+impl MotionOutput<LiveSink> {
+    /// Restart the timeline from now, after waiting on something other than the clock
+    pub fn reset_timeline(&mut self) {
+        self.sink.scheduler.reset();
+    }
+    pub fn jitter(&self) -> JitterStats {
+        self.sink.scheduler.jitter()
+    }
+}
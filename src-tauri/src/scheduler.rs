@@ -0,0 +1,87 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::winapi::TimerResolutionGuard;
+
+/// Below this much remaining time the scheduler spins instead of sleeping,
+/// since a sleep can overshoot by about a timer period even at 1ms resolution
+const SPIN_THRESHOLD: Duration = Duration::from_micros(1500);
+/// Falling further behind than this restarts the timeline instead of
+/// sending every missed tick in a burst
+const MAX_CATCH_UP: Duration = Duration::from_millis(50);
+
+/// How late the scheduler woke up relative to its deadlines
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default)]
+pub struct JitterStats {
+    pub ticks: u64,
+    pub mean_us: f64,
+    pub max_us: f64,
+}
+
+/// Waits on absolute deadlines so scheduling error never accumulates
+///
+/// Each `wait` targets the previous deadline plus the interval rather than
+/// now plus the interval, so a tick that lands late doesn't delay the rest.
+pub struct Scheduler {
+    next_deadline: Instant,
+    ticks: u64,
+    total_late: Duration,
+    max_late: Duration,
+    _timer_resolution: TimerResolutionGuard,
+}
+impl Scheduler {
+    /// Start a timeline at the current instant
+    pub fn start() -> Self {
+        Self {
+            next_deadline: Instant::now(),
+            ticks: 0,
+            total_late: Duration::ZERO,
+            max_late: Duration::ZERO,
+            _timer_resolution: TimerResolutionGuard::acquire(),
+        }
+    }
+    /// Restart the timeline from now, after waiting on something other than the clock
+    pub fn reset(&mut self) {
+        self.next_deadline = Instant::now();
+    }
+    /// Wait until `interval` after the previous deadline
+    pub fn wait(&mut self, interval: Duration) {
+        self.next_deadline += interval;
+        let late = sleep_until(self.next_deadline);
+
+        self.ticks += 1;
+        self.total_late += late;
+        self.max_late = self.max_late.max(late);
+
+        if late > MAX_CATCH_UP {
+            println!("Scheduler fell {}ms behind, restarting timeline", late.as_millis());
+            self.reset();
+        }
+    }
+    pub fn jitter(&self) -> JitterStats {
+        JitterStats {
+            ticks: self.ticks,
+            mean_us: if self.ticks == 0 { 0.0 } else { self.total_late.as_secs_f64() * 1_000_000.0 / self.ticks as f64 },
+            max_us: self.max_late.as_secs_f64() * 1_000_000.0,
+        }
+    }
+}
+
+/// Sleep until most of the way to `deadline`, then spin the rest,
+/// returning how late the deadline was actually reached
+pub fn sleep_until(deadline: Instant) -> Duration {
+    loop {
+        let now = Instant::now();
+        let Some(remaining) = deadline.checked_duration_since(now).filter(|r| !r.is_zero()) else {
+            return now.duration_since(deadline);
+        };
+
+        if remaining > SPIN_THRESHOLD {
+            thread::sleep(remaining - SPIN_THRESHOLD);
+        } else {
+            std::hint::spin_loop();
+        }
+    }
+}
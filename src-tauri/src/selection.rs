@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::firing::refresh_firing_context;
+use crate::types::{AppEvent, AppState, Game};
+
+/// Where the last selection of every game is kept, relative to the config directory
+const SELECTION_FILE: &str = "selection.json";
+/// How long to wait for further changes before writing, so held hotkeys don't write on every press
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The selected game, category, loadout and weapon, always read and replaced together
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct Selection {
+    pub game: usize,
+    pub category: usize,
+    pub loadout: usize,
+    pub weapon: usize,
+}
+impl Selection {
+    /// Number of weapon slots in the selected loadout, 0 if there is none
+    pub fn slot_count(&self, games: &[Game]) -> usize {
+        games.get(self.game)
+            .and_then(|game| game.categories.as_ref())
+            .and_then(|categories| categories.get(self.category))
+            .and_then(|category| category.loadouts.get(self.loadout))
+            .map(|loadout| loadout.slots.len())
+            .unwrap_or(0)
+    }
+    /// Keep the weapon slot within the selected loadout, e.g. after switching
+    /// from a loadout with more slots
+    pub fn clamp_weapon(self, games: &[Game]) -> Selection {
+        Selection {
+            weapon: self.weapon.min(self.slot_count(games).saturating_sub(1)),
+            ..self
+        }
+    }
+    /// Check that every index exists in `games`
+    ///
+    /// Games without categories (e.g. without a valid key), categories without loadouts
+    /// and loadouts without slots can only be selected at index 0. The default selection
+    /// is always valid.
+    pub fn validate(&self, games: &[Game]) -> Result<(), String> {
+        if *self == Selection::default() {
+            return Ok(());
+        }
+
+        let game = games.get(self.game)
+            .ok_or(format!("Game index {} not found", self.game))?;
+
+        let categories = game.categories.as_deref().unwrap_or_default();
+        match categories.get(self.category) {
+            Some(category) => {
+                if self.loadout != 0 && self.loadout >= category.loadouts.len() {
+                    return Err(format!("Loadout index {} not found in category `{}` in game `{}`", self.loadout, category.name, game.name));
+                }
+            },
+            None => {
+                if self.category != 0 || self.loadout != 0 {
+                    return Err(format!("Category index {} not found in game `{}`", self.category, game.name));
+                }
+            }
+        }
+
+        if self.weapon != 0 && self.weapon >= self.slot_count(games) {
+            return Err(format!("Weapon slot {} not found in the selected loadout", self.weapon));
+        }
+
+        Ok(())
+    }
+}
+
+/// What a `Selection` points at by name and ID, so it survives entries being added,
+/// removed or reordered (e.g. by a server merge)
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct SelectionIds {
+    /// Game name
+    pub game: Option<String>,
+    pub category: Option<String>,
+    pub loadout: Option<String>,
+    pub weapon: usize,
+}
+impl Selection {
+    pub fn ids(&self, games: &[Game]) -> SelectionIds {
+        let game = games.get(self.game);
+        let category = game
+            .and_then(|game| game.categories.as_ref())
+            .and_then(|categories| categories.get(self.category));
+        let loadout = category
+            .and_then(|category| category.loadouts.get(self.loadout));
+
+        SelectionIds {
+            game: game.map(|game| game.name.clone()),
+            category: category.map(|category| category.id.clone()),
+            loadout: loadout.map(|loadout| loadout.id.clone()),
+            weapon: self.weapon,
+        }
+    }
+}
+impl SelectionIds {
+    /// Find the indices these IDs are at now, falling back to the first
+    /// category or loadout for the ones that no longer exist
+    pub fn resolve(&self, games: &[Game]) -> Selection {
+        let Some((game_index, game)) = games.iter().enumerate()
+            .find(|(_, game)| Some(&game.name) == self.game.as_ref())
+        else {
+            return Selection::default();
+        };
+
+        let categories = game.categories.as_deref().unwrap_or_default();
+        let category_index = categories.iter()
+            .position(|category| Some(&category.id) == self.category.as_ref())
+            .unwrap_or(0);
+        let loadout_index = categories.get(category_index)
+            .and_then(|category| category.loadouts.iter().position(|loadout| Some(&loadout.id) == self.loadout.as_ref()))
+            .unwrap_or(0);
+
+        Selection {
+            game: game_index,
+            category: category_index,
+            loadout: loadout_index,
+            weapon: self.weapon,
+        }.clamp_weapon(games)
+    }
+}
+
+/// The last selection of every game, kept in `{config_dir}/selection.json`
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct SavedSelections {
+    /// Name of the game that was selected last
+    #[serde(default)]
+    pub last_game: Option<String>,
+    /// By game name
+    #[serde(default)]
+    pub games: HashMap<String, SelectionIds>,
+    /// Last loadout and weapon of each category, by game name and then category ID
+    #[serde(default)]
+    pub categories: HashMap<String, HashMap<String, SelectionIds>>,
+}
+impl SavedSelections {
+    /// What was last selected in `category_index` of the selection's game
+    fn category(&self, games: &[Game], selection: Selection, category_index: usize) -> Option<Selection> {
+        let game = games.get(selection.game)?;
+        let category = game.categories.as_ref()?.get(category_index)?;
+
+        self.categories.get(&game.name)?
+            .get(&category.id)
+            .map(|ids| ids.resolve(games))
+            .filter(|restored| restored.game == selection.game && restored.category == category_index)
+    }
+}
+impl SavedSelections {
+    /// Read the saved selections, starting over if the file is missing or unreadable
+    pub fn load(config_dir: &Path) -> Self {
+        let path = config_dir.join(SELECTION_FILE);
+        if !path.exists() {
+            return Self::default();
+        }
+
+        let result = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()));
+        match result {
+            Ok(saved) => saved,
+            Err(e) => {
+                eprintln!("Failed to load saved selection from {}, starting from the first game: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+    fn save(&self, config_dir: &Path) -> Result<(), String> {
+        std::fs::write(
+            config_dir.join(SELECTION_FILE),
+            serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize selection: {}", e))?
+        ).map_err(|e| format!("Failed to write selection file: {}", e))
+    }
+}
+
+/// Writes saved selections on its own thread, only the last of a burst of changes is written
+#[derive(Clone)]
+struct SelectionWriter {
+    sender: mpsc::Sender<SavedSelections>,
+}
+impl SelectionWriter {
+    fn spawn(config_dir: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel::<SavedSelections>();
+        thread::spawn(move || {
+            while let Ok(mut saved) = receiver.recv() {
+                while let Ok(newer) = receiver.recv_timeout(SAVE_DEBOUNCE) {
+                    saved = newer;
+                }
+
+                if let Err(e) = saved.save(&config_dir) {
+                    eprintln!("Failed to save selection: {}", e);
+                }
+            }
+        });
+
+        Self { sender }
+    }
+}
+
+#[derive(Default)]
+struct SelectionInner {
+    indices: Selection,
+    /// The source of truth, `indices` is re-resolved from it when the games change
+    ids: SelectionIds,
+    saved: SavedSelections,
+}
+/// Shared holder of the current `Selection`
+#[derive(Clone, Default)]
+pub struct SelectionState {
+    current: Arc<Mutex<SelectionInner>>,
+    /// Nothing is saved without one
+    writer: Option<SelectionWriter>,
+}
+impl SelectionState {
+    /// Start with nothing selected, restoring from `saved` once games are loaded
+    /// and saving changes back to `config_dir`
+    pub fn with_saved(saved: SavedSelections, config_dir: PathBuf) -> Self {
+        Self {
+            current: Arc::new(Mutex::new(SelectionInner {
+                saved,
+                ..Default::default()
+            })),
+            writer: Some(SelectionWriter::spawn(config_dir)),
+        }
+    }
+    pub fn get(&self) -> Selection {
+        self.current.lock().indices
+    }
+    /// What was last selected in the game named `game`
+    pub fn saved(&self, game: &str) -> Option<SelectionIds> {
+        self.current.lock().saved.games.get(game).cloned()
+    }
+}
+/// Publish `selection` and remember it for its game, saving to disk if that changed
+///
+/// Games without data (e.g. after a failed key check) keep what was remembered for them,
+/// so it can be restored once their data is back.
+fn store(
+    state: &AppState,
+    current: &mut SelectionInner,
+    selection: Selection,
+    games: &[Game],
+) {
+    let ids = selection.ids(games);
+    current.indices = selection;
+    current.ids = ids.clone();
+
+    let Some(game) = ids.game.clone() else { return; };
+    let saved = &mut current.saved;
+    let Some(category) = ids.category.clone() else {
+        if saved.last_game.as_ref() != Some(&game) {
+            saved.last_game = Some(game);
+            save_selections(state, saved);
+        }
+        return;
+    };
+    let category_saved = saved.categories.get(&game).and_then(|categories| categories.get(&category)) == Some(&ids);
+    if saved.last_game.as_ref() == Some(&game) && saved.games.get(&game) == Some(&ids) && category_saved {
+        return;
+    }
+    saved.last_game = Some(game.clone());
+    saved.categories.entry(game.clone()).or_default().insert(category, ids.clone());
+    saved.games.insert(game, ids);
+
+    save_selections(state, saved);
+}
+/// Hand the saved selections to the writer thread, the caller may be holding the selection lock
+fn save_selections(
+    state: &AppState,
+    saved: &SavedSelections,
+) {
+    if let Some(writer) = &state.selection.writer {
+        if writer.sender.send(saved.clone()).is_err() {
+            eprintln!("Failed to save selection: writer stopped");
+        }
+    }
+}
+
+/// Build a new selection from the current one, validate it against the loaded games
+/// and publish it, then refresh the firing context and announce what changed
+///
+/// Nothing is changed if `update` or validation fails.
+pub fn update_selection(
+    state: &AppState,
+    update: impl FnOnce(Selection, &[Game]) -> Result<Selection, String>,
+) -> Result<Selection, String> {
+    update_with_saved(state, |selection, games, _| update(selection, games))
+}
+/// Switch to the category `pick` returns, restoring the loadout and weapon
+/// last used in it, or otherwise starting from its first loadout
+pub fn enter_category(
+    state: &AppState,
+    pick: impl FnOnce(Selection, &[Game]) -> Result<usize, String>,
+) -> Result<Selection, String> {
+    update_with_saved(state, |selection, games, saved| {
+        let category_index = pick(selection, games)?;
+        if category_index == selection.category {
+            return Ok(selection);
+        }
+
+        Ok(saved.category(games, selection, category_index)
+            .unwrap_or(Selection {
+                category: category_index,
+                loadout: 0,
+                ..selection
+            }))
+    })
+}
+fn update_with_saved(
+    state: &AppState,
+    update: impl FnOnce(Selection, &[Game], &SavedSelections) -> Result<Selection, String>,
+) -> Result<Selection, String> {
+    let (previous, selection) = {
+        let games = state.games.read_arc();
+        let mut current = state.selection.current.lock();
+
+        let previous = current.indices;
+        let selection = update(previous, &games, &current.saved)?
+            .clamp_weapon(&games);
+        selection.validate(&games)?;
+        store(state, &mut current, selection, &games);
+
+        (previous, selection)
+    };
+
+    refresh_firing_context(state);
+    publish_changes(state, previous, selection);
+
+    Ok(selection)
+}
+/// Find the selected entries again after the games are replaced or edited,
+/// falling back to the first category or loadout if they no longer exist
+///
+/// If nothing was selected yet (e.g. on startup), the last saved selection is restored.
+pub fn revalidate_selection(
+    state: &AppState
+) {
+    let (previous, selection) = {
+        let games = state.games.read_arc();
+        let mut current = state.selection.current.lock();
+
+        let previous = current.indices;
+        let ids = match &current.ids.game {
+            Some(_) => current.ids.clone(),
+            None => current.saved.last_game.as_ref()
+                .and_then(|game| current.saved.games.get(game))
+                .cloned()
+                .unwrap_or_default(),
+        };
+        let mut selection = ids.resolve(&games);
+        if let Err(e) = selection.validate(&games) {
+            println!("Resetting selection: {}", e);
+            selection = Selection::default();
+        }
+
+        if ids.game.is_some() && ids != selection.ids(&games) {
+            // A fallback isn't remembered, the entries may be back with the next load
+            println!("Selection {:?} is not available, using {:?}", ids, selection.ids(&games));
+            current.indices = selection;
+            current.ids = ids;
+        } else {
+            store(state, &mut current, selection, &games);
+        }
+
+        (previous, selection)
+    };
+
+    refresh_firing_context(state);
+    publish_changes(state, previous, selection);
+}
+fn publish_changes(
+    state: &AppState,
+    previous: Selection,
+    selection: Selection,
+) {
+    if selection.game != previous.game {
+        state.events.publish(AppEvent::SwitchedGame { game_ind: selection.game });
+    }
+    if selection.category != previous.category {
+        state.events.publish(AppEvent::SwitchedCategory { category_ind: selection.category });
+    }
+    if selection.loadout != previous.loadout {
+        state.events.publish(AppEvent::SwitchedLoadout { loadout_ind: selection.loadout });
+    }
+    if selection.weapon != previous.weapon {
+        state.events.publish(AppEvent::SwitchedWeapon { weapon_ind: selection.weapon });
+    }
+}
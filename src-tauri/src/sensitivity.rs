@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+
+fn default_ratio() -> f32 {
+    1.0
+}
+
+/// The mouse and in-game settings a player (or the weapon data author) uses
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct SensitivityProfile {
+    pub dpi: f32,
+    pub sensitivity: f32,
+    /// In-game vertical sensitivity relative to horizontal
+    #[serde(default = "default_ratio")]
+    pub vertical_ratio: f32,
+    /// Horizontal field of view in degrees
+    pub fov: f32,
+    /// In-game ADS sensitivity modifier for magnified optics
+    #[serde(default = "default_ratio")]
+    pub optic_sensitivity: f32,
+}
+impl SensitivityProfile {
+    fn validate(&self, label: &str) -> Result<(), String> {
+        if self.dpi <= 0.0 {
+            return Err(format!("Invalid {} DPI: {}", label, self.dpi));
+        }
+        if self.sensitivity <= 0.0 {
+            return Err(format!("Invalid {} sensitivity: {}", label, self.sensitivity));
+        }
+        if self.vertical_ratio <= 0.0 {
+            return Err(format!("Invalid {} vertical ratio: {}", label, self.vertical_ratio));
+        }
+        if self.fov <= 0.0 || self.fov >= 180.0 {
+            return Err(format!("Invalid {} FOV: {}", label, self.fov));
+        }
+        if self.optic_sensitivity <= 0.0 {
+            return Err(format!("Invalid {} optic sensitivity: {}", label, self.optic_sensitivity));
+        }
+
+        Ok(())
+    }
+    /// Effective DPI, the product of mouse DPI and in-game sensitivity
+    pub fn edpi(&self) -> f32 {
+        self.dpi * self.sensitivity
+    }
+}
+
+/// How a game turns mouse counts into view rotation
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub enum SensitivityFormula {
+    /// Rotation per count depends only on sensitivity, FOV has no effect
+    #[default]
+    Linear,
+    /// Rotation per count scales with the focal length of the current FOV
+    FocalLength,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct SensitivityResult {
+    pub horizontal_multiplier: f32,
+    pub vertical_multiplier: f32,
+    pub acog_horizontal_multiplier: f32,
+    pub acog_vertical_multiplier: f32,
+}
+
+/// Ratio between the reference and user effective DPI
+///
+/// This is the factor weapon `dx`/`dy` values need to be scaled by
+/// so that a spray pulls the same distance for the user as for the author
+pub fn edpi_scale(
+    reference: &SensitivityProfile,
+    user: &SensitivityProfile,
+) -> f32 {
+    reference.edpi() / user.edpi()
+}
+
+/// Scale weapon data from the DPI and sensitivity it was tuned at to the user's
+///
+/// Each of DPI and sensitivity only contributes when both the reference and
+/// user value are known, so missing metadata leaves weapon data untouched
+pub fn reference_scale(
+    reference_dpi: Option<u32>,
+    reference_sensitivity: Option<f32>,
+    user_dpi: Option<u32>,
+    user_sensitivity: Option<f32>,
+) -> f32 {
+    let dpi_scale = match (reference_dpi, user_dpi) {
+        (Some(reference), Some(user)) if reference > 0 && user > 0 => reference as f32 / user as f32,
+        _ => 1.0,
+    };
+    let sensitivity_scale = match (reference_sensitivity, user_sensitivity) {
+        (Some(reference), Some(user)) if reference > 0.0 && user > 0.0 => reference / user,
+        _ => 1.0,
+    };
+
+    dpi_scale * sensitivity_scale
+}
+fn focal_length_scale(
+    reference_fov: f32,
+    user_fov: f32,
+) -> f32 {
+    (reference_fov.to_radians() / 2.0).tan() / (user_fov.to_radians() / 2.0).tan()
+}
+
+/// Derive the mouse multipliers for a user, given the settings the
+/// weapon data was tuned with
+///
+/// Weapon data is tuned unmagnified, so `optic_zoom` (e.g. 2.5 for an ACOG)
+/// scales the optic multipliers along with the difference in optic sensitivity.
+///
+/// `dpi_scale` is the part of the eDPI ratio the engine already applies (see
+/// `reference_scale`), it's divided out so the ratio is only applied once.
+pub fn calculate(
+    reference: &SensitivityProfile,
+    user: &SensitivityProfile,
+    optic_zoom: f32,
+    formula: SensitivityFormula,
+    dpi_scale: f32,
+) -> Result<SensitivityResult, String> {
+    reference.validate("reference")?;
+    user.validate("user")?;
+    if optic_zoom < 1.0 {
+        return Err(format!("Invalid optic zoom: {}", optic_zoom));
+    }
+    if !(dpi_scale.is_finite() && dpi_scale > 0.0) {
+        return Err(format!("Invalid DPI scale: {}", dpi_scale));
+    }
+
+    let fov_scale = match formula {
+        SensitivityFormula::Linear => 1.0,
+        SensitivityFormula::FocalLength => focal_length_scale(reference.fov, user.fov),
+    };
+
+    let horizontal_multiplier = edpi_scale(reference, user) / dpi_scale * fov_scale;
+    let vertical_multiplier = horizontal_multiplier * reference.vertical_ratio / user.vertical_ratio;
+
+    let optic_scale = optic_zoom * reference.optic_sensitivity / user.optic_sensitivity;
+
+    Ok(SensitivityResult {
+        horizontal_multiplier,
+        vertical_multiplier,
+        acog_horizontal_multiplier: horizontal_multiplier * optic_scale,
+        acog_vertical_multiplier: vertical_multiplier * optic_scale,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(dpi: f32, sensitivity: f32) -> SensitivityProfile {
+        SensitivityProfile {
+            dpi,
+            sensitivity,
+            vertical_ratio: 1.0,
+            fov: 90.0,
+            optic_sensitivity: 1.0,
+        }
+    }
+
+    #[test]
+    fn calculated_multipliers_and_dpi_scale_apply_the_edpi_ratio_once() {
+        let reference = profile(800.0, 10.0);
+        let user = profile(400.0, 5.0);
+        let edpi_ratio = edpi_scale(&reference, &user);
+
+        // Both, only one or neither of DPI and sensitivity can be covered by the engine
+        for (user_dpi, user_sensitivity) in [(Some(400), Some(5.0)), (Some(400), None), (None, Some(5.0)), (None, None)] {
+            let dpi_scale = reference_scale(Some(800), Some(10.0), user_dpi, user_sensitivity);
+            let result = calculate(&reference, &user, 2.5, SensitivityFormula::Linear, dpi_scale).unwrap();
+
+            assert!((result.horizontal_multiplier * dpi_scale - edpi_ratio).abs() < 1e-5);
+            assert!((result.vertical_multiplier * dpi_scale - edpi_ratio).abs() < 1e-5);
+            assert!((result.acog_horizontal_multiplier * dpi_scale - edpi_ratio * 2.5).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{AppEvent, AppState};
+
+fn default_multiplier() -> f32 {
+    1.0
+}
+
+/// How the player is standing in game, tracked from the crouch and prone keys
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum Stance {
+    Standing,
+    Crouching,
+    Prone,
+}
+impl Stance {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Crouching,
+            2 => Self::Prone,
+            _ => Self::Standing,
+        }
+    }
+}
+/// Whether a stance key has to be held, or switches the stance on every press
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub enum StanceKeyMode {
+    #[default]
+    Hold,
+    Toggle,
+}
+/// Horizontal and vertical factors applied on top of `recoil::movement_scale` in one stance
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct StanceMultipliers {
+    #[serde(default = "default_multiplier")]
+    pub horizontal_multiplier: f32,
+    #[serde(default = "default_multiplier")]
+    pub vertical_multiplier: f32,
+}
+impl Default for StanceMultipliers {
+    fn default() -> Self {
+        Self {
+            horizontal_multiplier: 1.0,
+            vertical_multiplier: 1.0,
+        }
+    }
+}
+/// Per-game multipliers for every stance but standing, which is never scaled
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default)]
+pub struct StanceModifiers {
+    #[serde(default)]
+    pub crouching: StanceMultipliers,
+    #[serde(default)]
+    pub prone: StanceMultipliers,
+}
+impl StanceModifiers {
+    pub fn get_mut(&mut self, stance: Stance) -> Option<&mut StanceMultipliers> {
+        match stance {
+            Stance::Standing => None,
+            Stance::Crouching => Some(&mut self.crouching),
+            Stance::Prone => Some(&mut self.prone),
+        }
+    }
+    /// Scale a shot's movement for `stance`
+    pub fn apply(&self, stance: Stance, scale: (f32, f32)) -> (f32, f32) {
+        let multipliers = match stance {
+            Stance::Standing => return scale,
+            Stance::Crouching => &self.crouching,
+            Stance::Prone => &self.prone,
+        };
+
+        (scale.0 * multipliers.horizontal_multiplier, scale.1 * multipliers.vertical_multiplier)
+    }
+}
+
+/// The current stance, written by the input listener and read by the recoil worker on every shot
+#[derive(Clone)]
+pub struct StanceState {
+    current: Arc<AtomicU8>,
+}
+impl Default for StanceState {
+    fn default() -> Self {
+        Self {
+            current: Arc::new(AtomicU8::new(Stance::Standing as u8)),
+        }
+    }
+}
+impl StanceState {
+    pub fn get(&self) -> Stance {
+        Stance::from_u8(self.current.load(Ordering::SeqCst))
+    }
+}
+
+/// Switch to `stance`, emitting `StanceChanged` if it's different from the current one
+pub fn set_stance(
+    state: &AppState,
+    stance: Stance
+) {
+    let previous = state.stance.current.swap(stance as u8, Ordering::SeqCst);
+    if previous != stance as u8 {
+        println!("Stance changed to {:?}", stance);
+        state.events.publish(AppEvent::StanceChanged { stance });
+    }
+}
+/// Update the stance for a press (`pressed`) or release of the key bound to `stance`
+///
+/// Held keys return to standing on release, unless another stance was entered meanwhile.
+/// Toggled keys switch on release, so key repeat doesn't toggle back and forth.
+pub fn handle_stance_key(
+    state: &AppState,
+    stance: Stance,
+    mode: StanceKeyMode,
+    pressed: bool
+) {
+    let current = state.stance.get();
+    match (mode, pressed) {
+        (StanceKeyMode::Hold, true) => set_stance(state, stance),
+        (StanceKeyMode::Hold, false) => if current == stance {
+            set_stance(state, Stance::Standing);
+        },
+        (StanceKeyMode::Toggle, true) => {},
+        (StanceKeyMode::Toggle, false) => set_stance(state, if current == stance { Stance::Standing } else { stance }),
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::types::AppEvent;
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct UsageStats {
+    pub sprays: u64,
+    pub shots: u64,
+    pub spray_ms: u64,
+}
+impl UsageStats {
+    fn merge(&mut self, other: &UsageStats) {
+        self.sprays += other.sprays;
+        self.shots += other.shots;
+        self.spray_ms += other.spray_ms;
+    }
+}
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct StatsSummary {
+    pub totals: UsageStats,
+    /// Keyed by weapon ID
+    pub weapons: HashMap<String, UsageStats>,
+    /// Keyed by `<category>/<loadout>`
+    pub loadouts: HashMap<String, UsageStats>,
+}
+impl StatsSummary {
+    fn record(&mut self, loadout: &str, weapon_id: &str, apply: impl Fn(&mut UsageStats)) {
+        apply(&mut self.totals);
+        apply(self.weapons.entry(weapon_id.to_string()).or_default());
+        apply(self.loadouts.entry(loadout.to_string()).or_default());
+    }
+    fn merge(&mut self, other: &StatsSummary) {
+        self.totals.merge(&other.totals);
+        for (weapon_id, usage) in &other.weapons {
+            self.weapons.entry(weapon_id.clone()).or_default().merge(usage);
+        }
+        for (loadout, usage) in &other.loadouts {
+            self.loadouts.entry(loadout.clone()).or_default().merge(usage);
+        }
+    }
+}
+/// Everything persisted for a game, one summary per (UTC) day
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct GameStats {
+    pub days: BTreeMap<String, StatsSummary>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum StatsScope {
+    Session,
+    Today,
+    AllTime,
+}
+
+struct ActiveSpray {
+    game: String,
+    loadout: String,
+    weapon_id: String,
+    day: String,
+    started: Instant,
+}
+
+/// Collects spray statistics, fed by the recoil worker through a `StatsRecorder`
+pub struct StatsTracker {
+    stats_dir: PathBuf,
+    session: HashMap<String, StatsSummary>,
+    history: HashMap<String, GameStats>,
+    active_spray: Option<ActiveSpray>,
+}
+impl StatsTracker {
+    pub fn new(config_dir: &Path) -> Self {
+        Self {
+            stats_dir: config_dir.join("stats"),
+            session: HashMap::new(),
+            history: HashMap::new(),
+            active_spray: None,
+        }
+    }
+    pub fn handle_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::StartedShooting { game, category, loadout, weapon_id, .. } => {
+                let spray = ActiveSpray {
+                    game: game.clone(),
+                    loadout: format!("{}/{}", category, loadout),
+                    weapon_id: weapon_id.clone(),
+                    day: today(),
+                    started: Instant::now(),
+                };
+                self.record(&spray.game, &spray.day, &spray.loadout, &spray.weapon_id, |usage| usage.sprays += 1);
+                self.active_spray = Some(spray);
+            },
+            AppEvent::ShotFired { weapon_id, .. } => {
+                let Some(spray) = self.active_spray.take() else { return; };
+
+                // Shots are counted against the weapon that fired them, even after a mid-spray switch
+                self.record(&spray.game, &spray.day, &spray.loadout, weapon_id, |usage| usage.shots += 1);
+                self.active_spray = Some(spray);
+            },
+            AppEvent::StoppedShooting => {
+                let Some(spray) = self.active_spray.take() else { return; };
+
+                let spray_ms = spray.started.elapsed().as_millis() as u64;
+                self.record(&spray.game, &spray.day, &spray.loadout, &spray.weapon_id, |usage| usage.spray_ms += spray_ms);
+
+                if let Err(e) = self.save(&spray.game) {
+                    eprintln!("Failed to save stats for game `{}`: {}", spray.game, e);
+                }
+            },
+            _ => {}
+        }
+    }
+    fn record(&mut self, game: &str, day: &str, loadout: &str, weapon_id: &str, apply: impl Fn(&mut UsageStats)) {
+        self.session.entry(game.to_string())
+            .or_default()
+            .record(loadout, weapon_id, &apply);
+        self.history_mut(game).days
+            .entry(day.to_string())
+            .or_default()
+            .record(loadout, weapon_id, &apply);
+    }
+    fn stats_path(&self, game: &str) -> PathBuf {
+        self.stats_dir.join(format!("{}.json", game))
+    }
+    /// Persisted stats for a game, loaded from disk on first use
+    fn history_mut(&mut self, game: &str) -> &mut GameStats {
+        if !self.history.contains_key(game) {
+            let path = self.stats_path(game);
+            let game_stats = match std::fs::read_to_string(&path) {
+                Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                    eprintln!("Failed to parse stats file {}: {}", path.display(), e);
+                    GameStats::default()
+                }),
+                Err(_) => GameStats::default(),
+            };
+            self.history.insert(game.to_string(), game_stats);
+        }
+
+        self.history.get_mut(game).expect("Stats were just loaded")
+    }
+    fn save(&self, game: &str) -> Result<(), String> {
+        let Some(game_stats) = self.history.get(game) else { return Ok(()); };
+
+        std::fs::create_dir_all(&self.stats_dir)
+            .map_err(|e| format!("Failed to create stats directory: {}", e))?;
+        std::fs::write(
+            self.stats_path(game),
+            serde_json::to_string_pretty(game_stats).map_err(|e| format!("Failed to serialize stats: {}", e))?
+        ).map_err(|e| format!("Failed to write stats file: {}", e))
+    }
+    pub fn summary(&mut self, game: &str, scope: StatsScope) -> StatsSummary {
+        match scope {
+            StatsScope::Session => self.session.get(game).cloned().unwrap_or_default(),
+            StatsScope::Today => self.history_mut(game).days.get(&today()).cloned().unwrap_or_default(),
+            StatsScope::AllTime => {
+                let mut summary = StatsSummary::default();
+                for day in self.history_mut(game).days.values() {
+                    summary.merge(day);
+                }
+                summary
+            }
+        }
+    }
+    pub fn daily(&mut self, game: &str) -> BTreeMap<String, StatsSummary> {
+        self.history_mut(game).days.clone()
+    }
+    /// Clear the session and persisted stats for one game, or for every game
+    pub fn reset(&mut self, game: Option<&str>) -> Result<(), String> {
+        match game {
+            Some(game) => {
+                self.session.remove(game);
+                self.history.insert(game.to_string(), GameStats::default());
+                let path = self.stats_path(game);
+                if path.exists() {
+                    std::fs::remove_file(&path)
+                        .map_err(|e| format!("Failed to remove stats file {}: {}", path.display(), e))?;
+                }
+            },
+            None => {
+                self.session.clear();
+                self.history.clear();
+                if self.stats_dir.exists() {
+                    std::fs::remove_dir_all(&self.stats_dir)
+                        .map_err(|e| format!("Failed to remove stats directory: {}", e))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Hands spray events to a `StatsTracker` on its own thread, so the recoil worker
+/// never waits on the stats lock or on stats files
+///
+/// Unlike an event bus subscription, no events are dropped when the tracker falls behind.
+#[derive(Clone)]
+pub struct StatsRecorder {
+    sender: mpsc::Sender<AppEvent>,
+}
+impl StatsRecorder {
+    pub fn spawn(stats: Arc<Mutex<StatsTracker>>) -> Self {
+        let (sender, receiver) = mpsc::channel::<AppEvent>();
+        thread::Builder::new()
+            .name("stats".to_string())
+            .spawn(move || {
+                while let Ok(event) = receiver.recv() {
+                    stats.lock().handle_event(&event);
+                }
+            })
+            .expect("Failed to spawn stats thread");
+
+        Self { sender }
+    }
+    pub fn record(&self, event: &AppEvent) {
+        if self.sender.send(event.clone()).is_err() {
+            eprintln!("Failed to record stats: stats thread stopped");
+        }
+    }
+}
+
+/// Today's UTC date as `YYYY-MM-DD`
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+
+    // Convert days since the epoch to a civil date (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -1,15 +1,29 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use std::sync::{mpsc::{Sender, Receiver}, atomic::AtomicUsize};
 use std::sync::{Arc, atomic::AtomicBool};
-use std::time::Instant;
 
+use arc_swap::ArcSwap;
 use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 
+use crate::events::EventBus;
+use crate::firing::FiringContext;
+use crate::game_data::IntegrityReport;
+use crate::local_api::{generate_token, LocalApiTask};
+use crate::motion::DEFAULT_TICK_RATE;
+use crate::scheduler::JitterStats;
+use crate::selection::SelectionState;
+use crate::sensitivity::{reference_scale, SensitivityFormula};
+use crate::stance::{Stance, StanceKeyMode, StanceModifiers, StanceState};
+use crate::stats::{StatsRecorder, StatsTracker};
+use crate::worker::{RecoilWorkerHandle, RecoilWorkerStatus};
+
 fn default_enabled() -> bool {
     true
 }
+fn default_multiplier() -> f32 {
+    1.0
+}
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct GridLayoutInfo {
@@ -24,8 +38,24 @@ impl Default for GridLayoutInfo {
     }
 }
 
+/// The currently selected weapon along with where it was selected from
+#[derive(Clone, Debug)]
+pub struct ActiveWeapon {
+    pub game: String,
+    pub category: String,
+    pub category_id: String,
+    pub loadout: String,
+    /// See `Loadout::id`, names aren't unique
+    pub loadout_id: String,
+    pub weapon_id: String,
+    /// IDs of the attachments selected for the weapon in its slot
+    pub attachments: Vec<String>,
+}
+
 pub struct LoadedGames {
     pub game_data: Vec<Game>,
+    /// Categories and loadouts that were given an ID while loading, see `game_data::assign_ids`
+    pub ids_assigned: usize,
 }
 #[derive(Clone, Deserialize, Serialize, Debug)]
 #[serde(tag = "type")]
@@ -41,16 +71,22 @@ pub enum KeyStatus {
 pub enum KeyStatusResponse {
     HWIDMismatch { key: String },
     Invalid { key: String },
-    Valid { key: String, timestamp: u64, config: Game },
+    Valid { key: String, timestamp: u64, config: Box<Game> },
     Expired { key: String, timestamp: u64 },
     Banned { key: String }
 }
-#[derive(Serialize, Deserialize)]
+/// Bumped whenever an existing event changes shape, new event kinds don't require a bump
+pub const EVENT_SCHEMA_VERSION: u32 = 3;
+
+#[derive(Clone, Serialize, Deserialize)]
 #[serde(tag = "event", content = "data")]
 pub enum AppEvent {
     UpdatedGames {
         games: Vec<Game>,
     },
+    SwitchedGame {
+        game_ind: usize,
+    },
     SwitchedWeapon {
         weapon_ind: usize,
     },
@@ -61,9 +97,83 @@ pub enum AppEvent {
         category_ind: usize,
     },
     StartedShooting {
-        weapon_ind: usize
+        weapon_ind: usize,
+        game: String,
+        category: String,
+        /// Stable IDs of the category and loadout, their names can change
+        category_id: String,
+        loadout: String,
+        loadout_id: String,
+        weapon_id: String,
+    },
+    ShotFired {
+        weapon_id: String,
+        shot_index: u32,
     },
-    StoppedShooting
+    StoppedShooting,
+    Saved,
+    LicenseStatusChanged {
+        game: String,
+        key_status: Option<KeyStatus>,
+    },
+    EngineError {
+        message: String,
+    },
+    PausedChanged {
+        paused: bool,
+    },
+    RecoilWorkerStatusChanged {
+        status: RecoilWorkerStatus,
+    },
+    SprayTiming {
+        weapon_id: String,
+        jitter: JitterStats,
+    },
+    /// Games with errors after loading or checking, see `game_data::check_game`
+    GameDataIssues {
+        reports: Vec<IntegrityReport>,
+    },
+    StanceChanged {
+        stance: Stance,
+    },
+}
+impl AppEvent {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppEvent::UpdatedGames { .. } => "UpdatedGames",
+            AppEvent::SwitchedGame { .. } => "SwitchedGame",
+            AppEvent::SwitchedWeapon { .. } => "SwitchedWeapon",
+            AppEvent::SwitchedLoadout { .. } => "SwitchedLoadout",
+            AppEvent::SwitchedCategory { .. } => "SwitchedCategory",
+            AppEvent::StartedShooting { .. } => "StartedShooting",
+            AppEvent::ShotFired { .. } => "ShotFired",
+            AppEvent::StoppedShooting => "StoppedShooting",
+            AppEvent::Saved => "Saved",
+            AppEvent::LicenseStatusChanged { .. } => "LicenseStatusChanged",
+            AppEvent::EngineError { .. } => "EngineError",
+            AppEvent::PausedChanged { .. } => "PausedChanged",
+            AppEvent::RecoilWorkerStatusChanged { .. } => "RecoilWorkerStatusChanged",
+            AppEvent::SprayTiming { .. } => "SprayTiming",
+            AppEvent::GameDataIssues { .. } => "GameDataIssues",
+            AppEvent::StanceChanged { .. } => "StanceChanged",
+        }
+    }
+}
+/// What subscribers outside the process (webview, local API) receive,
+/// so they can check `schema_version` and skip event kinds they don't know
+#[derive(Clone, Serialize)]
+pub struct EventEnvelope {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub event: AppEvent,
+}
+impl From<AppEvent> for EventEnvelope {
+    fn from(event: AppEvent) -> Self {
+        Self {
+            schema_version: EVENT_SCHEMA_VERSION,
+            event,
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -71,7 +181,17 @@ pub struct KeybindConfig {
     pub require_right_hold: bool,
     pub primary_weapon: char,
     pub secondary_weapon: char,
+    /// Switch keys for the third weapon slot onwards
+    #[serde(default = "default_extra_slot_keys")]
+    pub extra_slot_keys: Vec<char>,
     pub alternative_fire: char,
+    #[serde(default = "default_crouch_key")]
+    pub crouch: char,
+    #[serde(default = "default_prone_key")]
+    pub prone: char,
+    /// Whether the crouch and prone keys are held or toggle the stance
+    #[serde(default)]
+    pub stance_key_mode: StanceKeyMode,
 }
 impl Default for KeybindConfig {
     fn default() -> Self {
@@ -79,10 +199,30 @@ impl Default for KeybindConfig {
             require_right_hold: true,
             primary_weapon: '1',
             secondary_weapon: '2',
+            extra_slot_keys: default_extra_slot_keys(),
             alternative_fire: 'm',
+            crouch: default_crouch_key(),
+            prone: default_prone_key(),
+            stance_key_mode: StanceKeyMode::default(),
         }
     }
 }
+impl KeybindConfig {
+    /// The switch key of every weapon slot, in slot order
+    pub fn slot_keys(&self) -> impl Iterator<Item = char> + '_ {
+        [self.primary_weapon, self.secondary_weapon].into_iter()
+            .chain(self.extra_slot_keys.iter().copied())
+    }
+}
+fn default_extra_slot_keys() -> Vec<char> {
+    vec!['3', '4']
+}
+fn default_crouch_key() -> char {
+    'c'
+}
+fn default_prone_key() -> char {
+    'z'
+}
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MouseConfig {
     pub horizontal_multiplier: f32,
@@ -93,6 +233,15 @@ pub struct MouseConfig {
     pub acog_vertical_multiplier: f32,
     #[serde(default = "default_enabled")]
     pub scroll_wheel_weapon_swap: bool,
+    /// The user's mouse DPI, weapon data is rescaled from the game's `reference_dpi`
+    #[serde(default)]
+    pub dpi: Option<u32>,
+    /// The user's in-game sensitivity, weapon data is rescaled from the game's `reference_sensitivity`
+    #[serde(default)]
+    pub sensitivity: Option<f32>,
+    /// How many times per second mouse moves are sent while firing
+    #[serde(default = "default_tick_rate")]
+    pub tick_rate: u32,
 }
 impl Default for MouseConfig {
     fn default() -> Self {
@@ -102,24 +251,163 @@ impl Default for MouseConfig {
             acog_horizontal_multiplier: 1.0,
             acog_vertical_multiplier: 1.0,
             scroll_wheel_weapon_swap: true,
+            dpi: None,
+            sensitivity: None,
+            tick_rate: DEFAULT_TICK_RATE,
         }
     }
 }
+fn default_tick_rate() -> u32 {
+    DEFAULT_TICK_RATE
+}
+fn default_local_api_port() -> u16 {
+    4778
+}
+/// Opt-in control server for external tools, only ever bound to localhost
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LocalApiConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_local_api_port")]
+    pub port: u16,
+    #[serde(default = "generate_token")]
+    pub token: String,
+}
+impl Default for LocalApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_local_api_port(),
+            token: generate_token(),
+        }
+    }
+}
+/// Per-game keybind overrides, any field left as `None` falls back to the global value
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct KeybindOverrides {
+    pub require_right_hold: Option<bool>,
+    pub primary_weapon: Option<char>,
+    pub secondary_weapon: Option<char>,
+    #[serde(default)]
+    pub extra_slot_keys: Option<Vec<char>>,
+    pub alternative_fire: Option<char>,
+    #[serde(default)]
+    pub crouch: Option<char>,
+    #[serde(default)]
+    pub prone: Option<char>,
+    #[serde(default)]
+    pub stance_key_mode: Option<StanceKeyMode>,
+}
+impl KeybindOverrides {
+    pub fn apply(&self, base: &KeybindConfig) -> KeybindConfig {
+        KeybindConfig {
+            require_right_hold: self.require_right_hold.unwrap_or(base.require_right_hold),
+            primary_weapon: self.primary_weapon.unwrap_or(base.primary_weapon),
+            secondary_weapon: self.secondary_weapon.unwrap_or(base.secondary_weapon),
+            extra_slot_keys: self.extra_slot_keys.clone().unwrap_or_else(|| base.extra_slot_keys.clone()),
+            alternative_fire: self.alternative_fire.unwrap_or(base.alternative_fire),
+            crouch: self.crouch.unwrap_or(base.crouch),
+            prone: self.prone.unwrap_or(base.prone),
+            stance_key_mode: self.stance_key_mode.unwrap_or(base.stance_key_mode),
+        }
+    }
+}
+/// Per-game mouse multiplier overrides, any field left as `None` falls back to the global value
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct MouseOverrides {
+    pub horizontal_multiplier: Option<f32>,
+    pub vertical_multiplier: Option<f32>,
+    pub acog_horizontal_multiplier: Option<f32>,
+    pub acog_vertical_multiplier: Option<f32>,
+    #[serde(default)]
+    pub sensitivity: Option<f32>,
+}
+impl MouseOverrides {
+    pub fn apply(&self, base: &MouseConfig) -> MouseConfig {
+        MouseConfig {
+            horizontal_multiplier: self.horizontal_multiplier.unwrap_or(base.horizontal_multiplier),
+            vertical_multiplier: self.vertical_multiplier.unwrap_or(base.vertical_multiplier),
+            acog_horizontal_multiplier: self.acog_horizontal_multiplier.unwrap_or(base.acog_horizontal_multiplier),
+            acog_vertical_multiplier: self.acog_vertical_multiplier.unwrap_or(base.acog_vertical_multiplier),
+            sensitivity: self.sensitivity.or(base.sensitivity),
+            ..base.clone()
+        }
+    }
+}
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct GameSettings {
+    #[serde(default)]
+    pub keybinds: KeybindOverrides,
+    #[serde(default)]
+    pub mouse_config: MouseOverrides,
+    /// Multipliers applied while crouching or prone
+    #[serde(default)]
+    pub stance_modifiers: StanceModifiers,
+}
 #[derive(Clone, Serialize, Deserialize)]
 pub struct GlobalConfig {
     #[serde(default)]
     pub keybinds: KeybindConfig,
     #[serde(default)]
     pub mouse_config: MouseConfig,
+    /// Overrides keyed by `Game::name`
+    #[serde(default)]
+    pub game_settings: HashMap<String, GameSettings>,
+    #[serde(default)]
+    pub local_api: LocalApiConfig,
 }
 impl Default for GlobalConfig {
     fn default() -> Self {
         Self {
             keybinds: KeybindConfig::default(),
             mouse_config: MouseConfig::default(),
+            game_settings: HashMap::new(),
+            local_api: LocalApiConfig::default(),
         }
     }
 }
+impl GlobalConfig {
+    /// Resolve the settings the engine should use for a game,
+    /// layering that game's overrides (if any) on top of the global values
+    pub fn effective(&self, game: Option<&Game>) -> EffectiveConfig {
+        let (keybinds, mouse_config, stance_modifiers) = match game.and_then(|game| self.game_settings.get(&game.name)) {
+            Some(settings) => (
+                settings.keybinds.apply(&self.keybinds),
+                settings.mouse_config.apply(&self.mouse_config),
+                settings.stance_modifiers,
+            ),
+            None => (
+                self.keybinds.clone(),
+                self.mouse_config.clone(),
+                StanceModifiers::default(),
+            ),
+        };
+
+        let dpi_scale = match game {
+            Some(game) => reference_scale(
+                game.reference_dpi, game.reference_sensitivity,
+                mouse_config.dpi, mouse_config.sensitivity,
+            ),
+            None => 1.0,
+        };
+
+        EffectiveConfig {
+            keybinds,
+            mouse_config,
+            dpi_scale,
+            stance_modifiers,
+        }
+    }
+}
+#[derive(Clone, Serialize)]
+pub struct EffectiveConfig {
+    pub keybinds: KeybindConfig,
+    pub mouse_config: MouseConfig,
+    /// Scale from the DPI and sensitivity the weapon data was tuned at to the user's
+    pub dpi_scale: f32,
+    /// See `stance::StanceModifiers::apply`
+    pub stance_modifiers: StanceModifiers,
+}
 
 #[derive(Clone)]
 pub struct AppState {
@@ -127,20 +415,26 @@ pub struct AppState {
     pub global_config:   Arc<RwLock<GlobalConfig>>,
     pub config_dir_path: Arc<PathBuf>,
     
-    pub events_channel_sender:   Arc<Sender<AppEvent>>,
-    pub events_channel_reciever: Arc<Mutex<Receiver<AppEvent>>>,
+    pub events: EventBus,
 
+    pub paused:                 Arc<AtomicBool>,
     pub left_hold_active:       Arc<AtomicBool>,
     pub right_hold_active:      Arc<AtomicBool>,
-    pub current_game_index:     Arc<AtomicUsize>,
-    pub current_category_index: Arc<AtomicUsize>,
-    pub current_loadout_index:  Arc<AtomicUsize>,
-    pub current_weapon_index:   Arc<AtomicUsize>,
+    pub selection:              SelectionState,
+    pub stance:                 StanceState,
 
     pub grid_layout_info: Arc<RwLock<GridLayoutInfo>>,
+
+    // Resolved weapon and settings for the current selection, see `firing::refresh_firing_context`
+    pub firing_context: Arc<ArcSwap<FiringContext>>,
     
-    // Track last shot time per weapon ID for SingleFire trigger cap
-    pub last_shot_times: Arc<RwLock<HashMap<String, Instant>>>,
+    // Thread that fires the active weapon, owns SingleFire trigger cap timing
+    pub recoil_worker: RecoilWorkerHandle,
+
+    pub stats: Arc<Mutex<StatsTracker>>,
+    pub stats_recorder: StatsRecorder,
+
+    pub local_api_task: Arc<Mutex<Option<LocalApiTask>>>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SingleShotConfig {
@@ -183,23 +477,168 @@ pub struct Game {
     pub key:        Option<String>,
     pub key_status: Option<KeyStatus>,
     pub categories: Option<Vec<Category>>,
-    pub weapons:    Option<HashMap<String, Weapon>>,
+    pub weapons:    Option<HashMap<String, WeaponEntry>>,
+    /// Attachments loadouts can select for their weapons, by ID
+    #[serde(default)]
+    pub attachments: HashMap<String, Attachment>,
+    #[serde(default)]
+    pub sensitivity_formula: Option<SensitivityFormula>,
+    /// Mouse DPI the weapon `dx`/`dy` values were tuned at
+    #[serde(default)]
+    pub reference_dpi: Option<u32>,
+    /// In-game sensitivity the weapon `dx`/`dy` values were tuned at
+    #[serde(default)]
+    pub reference_sensitivity: Option<f32>,
+    /// Server items the user removed, which `merge_game_configs` must not add back
+    #[serde(default)]
+    pub removed: RemovedIds,
+}
+impl Game {
+    /// Basic game info only, used whenever the server has not validated a key for this game
+    pub fn without_config(
+        name: String,
+        key: Option<String>,
+        key_status: Option<KeyStatus>,
+    ) -> Self {
+        Self {
+            name,
+            key,
+            key_status,
+            categories: None,
+            weapons: None,
+            attachments: HashMap::new(),
+            sensitivity_formula: None,
+            reference_dpi: None,
+            reference_sensitivity: None,
+            removed: RemovedIds::default(),
+        }
+    }
+}
+/// IDs of deleted categories, loadouts, weapons and attachments, kept so the server copies aren't merged back in
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct RemovedIds {
+    #[serde(default)]
+    pub categories: HashSet<String>,
+    #[serde(default)]
+    pub loadouts: HashSet<String>,
+    #[serde(default)]
+    pub weapons: HashSet<String>,
+    #[serde(default)]
+    pub attachments: HashSet<String>,
 }
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Category {
+    /// Stable identifier, generated from the name when missing (see `game_data::assign_ids`)
+    #[serde(default)]
+    pub id: String,
     pub name: String,
     pub loadouts: Vec<Loadout>,
 }
+/// A named group of interchangeable weapons in a loadout, e.g. "Primary" or "Gadget"
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WeaponSlot {
+    pub name: String,
+    pub weapons: Vec<String>,
+    #[serde(default)]
+    pub selected: usize,
+    /// IDs of the attachments used with each weapon in the slot, by weapon ID
+    #[serde(default)]
+    pub attachments: HashMap<String, Vec<String>>,
+}
+impl WeaponSlot {
+    pub fn selected_weapon(&self) -> Option<&String> {
+        self.weapons.get(self.selected)
+    }
+    /// Remove a weapon along with its attachments, keeping the same weapon selected
+    /// (or the one that took the removed one's place)
+    pub fn remove_weapon(&mut self, weapon_id: &str) -> bool {
+        let Some(index) = self.weapons.iter().position(|id| id == weapon_id) else { return false; };
+
+        self.weapons.remove(index);
+        self.attachments.remove(weapon_id);
+        if index < self.selected {
+            self.selected -= 1;
+        }
+        self.selected = self.selected.min(self.weapons.len().saturating_sub(1));
+
+        true
+    }
+}
+/// A grip, barrel, muzzle device, etc. that changes a weapon's recoil
+///
+/// Multipliers of all of a weapon's attachments are combined first,
+/// then their offsets are added.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Attachment {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Applied to the weapon's `dx`
+    #[serde(default = "default_multiplier")]
+    pub horizontal_multiplier: f32,
+    /// Applied to the weapon's `dy`
+    #[serde(default = "default_multiplier")]
+    pub vertical_multiplier: f32,
+    /// Added to the weapon's `dx` after multiplying
+    #[serde(default)]
+    pub dx_offset: f32,
+    /// Added to the weapon's `dy` after multiplying
+    #[serde(default)]
+    pub dy_offset: f32,
+}
 #[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(from = "LoadoutData")]
 pub struct Loadout {
+    /// Stable identifier, unique within the game (see `game_data::assign_ids`)
+    #[serde(default)]
+    pub id: String,
     pub name: String,
     pub icon_url: Option<String>,
     #[serde(default)]
     pub icon_only: bool,
-    pub primaries: Vec<String>,
-    pub secondaries: Vec<String>,
-    pub selected_primary: usize,
-    pub selected_secondary: usize,
+    /// In switch key order, see `KeybindConfig::slot_keys`
+    pub slots: Vec<WeaponSlot>,
+}
+/// A `Loadout` as stored, which may still use the `primaries`/`secondaries`
+/// format from before loadouts had slots (including data from the server)
+#[derive(Deserialize)]
+struct LoadoutData {
+    #[serde(default)]
+    id: String,
+    name: String,
+    icon_url: Option<String>,
+    #[serde(default)]
+    icon_only: bool,
+    #[serde(default)]
+    slots: Vec<WeaponSlot>,
+    #[serde(default)]
+    primaries: Vec<String>,
+    #[serde(default)]
+    secondaries: Vec<String>,
+    #[serde(default)]
+    selected_primary: usize,
+    #[serde(default)]
+    selected_secondary: usize,
+}
+impl From<LoadoutData> for Loadout {
+    fn from(data: LoadoutData) -> Self {
+        let slots = if data.slots.is_empty() && !(data.primaries.is_empty() && data.secondaries.is_empty()) {
+            vec![
+                WeaponSlot { name: "Primary".to_string(), weapons: data.primaries, selected: data.selected_primary, attachments: HashMap::new() },
+                WeaponSlot { name: "Secondary".to_string(), weapons: data.secondaries, selected: data.selected_secondary, attachments: HashMap::new() },
+            ]
+        } else {
+            data.slots
+        };
+
+        Self {
+            id: data.id,
+            name: data.name,
+            icon_url: data.icon_url,
+            icon_only: data.icon_only,
+            slots,
+        }
+    }
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "type", content = "config")]  
@@ -207,4 +646,97 @@ pub enum Weapon {
     SingleFire(SingleFireConfig),
     SingleShot(SingleShotConfig),
     FullAutoStandard(FullAutoStandardConfig),
+}
+impl Weapon {
+    /// A new weapon of the variant named `kind` (as in the `type` tag), with no recoil to compensate
+    pub fn with_defaults(kind: &str, name: String) -> Result<Self, String> {
+        match kind {
+            "SingleFire" => Ok(Weapon::SingleFire(SingleFireConfig {
+                name,
+                description: None,
+                trigger_delay_ms: 100,
+                recoil_completion_ms: 50,
+                release_delay_ms: 50,
+                dx: 0.0,
+                dy: 0.0,
+                autofire: false,
+                enabled: true,
+            })),
+            "SingleShot" => Ok(Weapon::SingleShot(SingleShotConfig {
+                name,
+                description: None,
+                recoil_completion_ms: 50,
+                dx: 0.0,
+                dy: 0.0,
+                enabled: true,
+            })),
+            "FullAutoStandard" => Ok(Weapon::FullAutoStandard(FullAutoStandardConfig {
+                name,
+                description: None,
+                rpm: 600,
+                first_shot_scale: 1.0,
+                exponential_factor: 1.0,
+                dx: 0.0,
+                dy: 0.0,
+                enabled: true,
+            })),
+            _ => Err(format!("Unknown weapon type `{}`", kind)),
+        }
+    }
+    /// The per-shot `dx`/`dy`, which every weapon type has
+    pub fn offsets_mut(&mut self) -> (&mut f32, &mut f32) {
+        match self {
+            Weapon::SingleFire(config) => (&mut config.dx, &mut config.dy),
+            Weapon::SingleShot(config) => (&mut config.dx, &mut config.dy),
+            Weapon::FullAutoStandard(config) => (&mut config.dx, &mut config.dy),
+        }
+    }
+    pub fn set_name(&mut self, name: String) {
+        match self {
+            Weapon::SingleFire(config) => config.name = name,
+            Weapon::SingleShot(config) => config.name = name,
+            Weapon::FullAutoStandard(config) => config.name = name,
+        }
+    }
+}
+/// A weapon as stored in `Game::weapons`, either complete or a variant of another weapon
+///
+/// Variants are resolved into a `Weapon` with `game_data::resolved_weapon`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum WeaponEntry {
+    Weapon(Weapon),
+    Variant(WeaponVariant),
+}
+/// A weapon that takes everything it doesn't override from its base, so fixing
+/// the base fixes all of its variants
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WeaponVariant {
+    /// ID of the weapon this one is based on, which may itself be a variant
+    pub base: String,
+    /// Fields of the base weapon's `config` to replace, e.g. `{ "dy": 2.5 }`
+    #[serde(default)]
+    pub overrides: serde_json::Map<String, serde_json::Value>,
+}
+impl WeaponEntry {
+    pub fn set_name(&mut self, name: String) {
+        match self {
+            WeaponEntry::Weapon(weapon) => weapon.set_name(name),
+            WeaponEntry::Variant(variant) => {
+                variant.overrides.insert("name".to_string(), serde_json::Value::String(name));
+            },
+        }
+    }
+    /// ID of the weapon this one is based on, if it's a variant
+    pub fn base(&self) -> Option<&str> {
+        match self {
+            WeaponEntry::Weapon(_) => None,
+            WeaponEntry::Variant(variant) => Some(&variant.base),
+        }
+    }
+}
+impl From<Weapon> for WeaponEntry {
+    fn from(weapon: Weapon) -> Self {
+        WeaponEntry::Weapon(weapon)
+    }
 }
\ No newline at end of file
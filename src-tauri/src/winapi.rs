@@ -1,8 +1,12 @@
 extern crate winapi;
 
-use crate::{get_weapon_id, save_data};
-use crate::recoil::{handle_hold_lmb, clear_current_weapon_timing};
-use crate::types::{AppEvent, AppState, Weapon};
+use crate::{get_weapon_id, report_engine_error, save_data};
+use crate::commands::selection::select_weapon;
+use crate::selection::{update_selection, Selection};
+use crate::stance::{handle_stance_key, Stance};
+use crate::worker::{clear_current_weapon_timing, RecoilCommand};
+use crate::game_data::resolved_weapon;
+use crate::types::{AppEvent, AppState, WeaponEntry};
 
 use std::time::Duration;
 use std::{mem, ptr, thread};
@@ -13,6 +17,7 @@ use winapi::shared::windef::*;
 use winapi::um::libloaderapi::GetModuleHandleW;
 use winapi::um::winuser::*;
 use winapi::um::fileapi::GetVolumeInformationW;
+use winapi::um::timeapi::{timeBeginPeriod, timeEndPeriod};
 
 pub fn get_hardware_identifier() -> String {
     unsafe {
@@ -61,6 +66,20 @@ pub fn get_hardware_identifier() -> String {
     }
 }
 
+/// Raises the system timer resolution to 1ms for as long as it is alive,
+/// so sleeps wake up close to when they were asked to
+pub struct TimerResolutionGuard;
+impl TimerResolutionGuard {
+    pub fn acquire() -> Self {
+        unsafe { timeBeginPeriod(1); }
+        Self
+    }
+}
+impl Drop for TimerResolutionGuard {
+    fn drop(&mut self) {
+        unsafe { timeEndPeriod(1); }
+    }
+}
 pub fn press_key (
     key: char
 ) {
@@ -140,6 +159,29 @@ pub fn char_to_vk(c: char) -> u16 {
         (return_value & 0xFF) as u16
     }
 }
+/// Hotkeys `wnd_proc` handles on its own, which can't be used as weapon slot keys
+const RESERVED_KEYS: [(i32, &str); 9] = [
+    (VK_PRIOR, "Page Up"),
+    (VK_NEXT, "Page Down"),
+    (VK_HOME, "Home"),
+    (VK_END, "End"),
+    (VK_LEFT, "Left"),
+    (VK_RIGHT, "Right"),
+    (VK_UP, "Up"),
+    (VK_DOWN, "Down"),
+    (VK_INSERT, "Insert"),
+];
+/// The hotkey `c` is on the current keyboard layout, if it's one of `RESERVED_KEYS`
+pub fn reserved_key_name(c: char) -> Option<&'static str> {
+    let return_value = unsafe { VkKeyScanExW(c as u16, GetKeyboardLayout(0)) };
+    if return_value == -1 {
+        return None;
+    }
+
+    RESERVED_KEYS.iter()
+        .find(|(vk, _)| *vk == (return_value & 0xFF) as i32)
+        .map(|(_, name)| *name)
+}
 unsafe extern "system" fn wnd_proc(
     hwnd: HWND,
     msg: UINT,
@@ -209,19 +251,17 @@ unsafe extern "system" fn wnd_proc(
                     if flags & RI_MOUSE_LEFT_BUTTON_DOWN != 0 {
                         println!(":3 [LMB] v");
 
-                        // If the hold is not already active, start a new thread
-                        if !state.left_hold_active.load(Ordering::SeqCst) {
-                            state.left_hold_active.store(true, Ordering::SeqCst);
-
-                            let state_cloned = state.clone();
-                            thread::spawn(|| { handle_hold_lmb(
-                                state_cloned
-                            ) });
+                        // If the hold is not already active, wake the recoil worker
+                        if !state.left_hold_active.swap(true, Ordering::SeqCst)
+                            && !state.recoil_worker.send(RecoilCommand::Press)
+                        {
+                            eprintln!("Recoil worker is not running, ignoring trigger press");
                         }
                     }
                     if flags & RI_MOUSE_LEFT_BUTTON_UP != 0 {
                         println!(":3 [LMB] ^");
                         state.left_hold_active.store(false, Ordering::SeqCst);
+                        state.recoil_worker.send(RecoilCommand::Release);
                     }
 
                     // Handle mouse wheel events
@@ -232,7 +272,7 @@ unsafe extern "system" fn wnd_proc(
                         println!(":3 [WHEEL] {}", if scroll_up { "UP" } else { "DOWN" });
                         
                         // Check if scroll wheel weapon swap is enabled before cycling
-                        if state.global_config.read_arc().mouse_config.scroll_wheel_weapon_swap {
+                        if state.firing_context.load().config.mouse_config.scroll_wheel_weapon_swap {
                             // Cycle weapon type based on scroll direction
                             if let Err(e) = crate::cycle_weapon_type(state, scroll_up) {
                                 eprintln!("Failed to cycle weapon type: {}", e);
@@ -254,41 +294,40 @@ unsafe extern "system" fn wnd_proc(
                 if !state_ptr.is_null() {
                     let state: &AppState = unsafe { &*state_ptr };
 
-                    let global_config = state.global_config.read_arc().clone();
-                    let primary_key   = char_to_vk(global_config.keybinds.primary_weapon);
-                    let secondary_key = char_to_vk(global_config.keybinds.secondary_weapon);
+                    // Stance keys are tracked on both press and release, for hold mode
+                    let (stance, stance_key_mode) = {
+                        let keybinds = &state.firing_context.load().config.keybinds;
+                        let stance = if keyboard.VKey == char_to_vk(keybinds.crouch) {
+                            Some(Stance::Crouching)
+                        } else if keyboard.VKey == char_to_vk(keybinds.prone) {
+                            Some(Stance::Prone)
+                        } else {
+                            None
+                        };
+                        (stance, keybinds.stance_key_mode)
+                    };
+                    if let Some(stance) = stance {
+                        handle_stance_key(state, stance, stance_key_mode, flags as u32 & RI_KEY_BREAK == 0);
+                        return 0;
+                    }
 
                     if flags as u32 & RI_KEY_BREAK == 0 {
                         return 0; // Ignore key press events
                     }
 
-                    if keyboard.VKey == primary_key {
-                        println!("Switching to weapon 1");
-                        
-                        // Clear shot timing for trigger cap when switching weapons
-                        clear_current_weapon_timing(state);
-                        
-                        state.current_weapon_index.store(0, Ordering::SeqCst);
+                    // Each weapon slot has its own switch key
+                    let slot_index = state.firing_context.load().config.keybinds.slot_keys()
+                        .position(|key| char_to_vk(key) == keyboard.VKey);
 
-                        // Emit an event that the weapon has been switched
-                        if let Err(e) = state.events_channel_sender.send(AppEvent::SwitchedWeapon {
-                            weapon_ind: 0,
-                        }) {
-                            eprintln!("Failed to send event: {}", e);
-                        }
-                    } else if keyboard.VKey == secondary_key {
-                        println!("Switching to weapon 2");
+                    if let Some(slot_index) = slot_index {
+                        println!("Switching to weapon {}", slot_index + 1);
                         
                         // Clear shot timing for trigger cap when switching weapons
                         clear_current_weapon_timing(state);
                         
-                        state.current_weapon_index.store(1, Ordering::SeqCst);
-
-                        // Emit an event that the weapon has been switched
-                        if let Err(e) = state.events_channel_sender.send(AppEvent::SwitchedWeapon {
-                            weapon_ind: 1,
-                        }) {
-                            eprintln!("Failed to send event: {}", e);
+                        // Emits `SwitchedWeapon` if the weapon changed
+                        if let Err(e) = select_weapon(state, slot_index) {
+                            eprintln!("Failed to switch weapon: {}", e);
                         }
                     } else if keyboard.VKey as i32 == VK_PRIOR || keyboard.VKey as i32 == VK_NEXT 
                         || keyboard.VKey as i32 == VK_HOME || keyboard.VKey as i32 == VK_END
@@ -296,46 +335,54 @@ unsafe extern "system" fn wnd_proc(
                         let current_weapon_id = match get_weapon_id(state) {
                             Ok(id) => id,
                             Err(e) => {
-                                eprintln!("Error getting weapon ID: {}", e);
+                                report_engine_error(state, format!("Error getting weapon ID: {}", e));
                                 return 0;
                             }
                         };
                         let mut games = state.games.write_arc();
-                        let current_game = if let Some(game) = games.get_mut(state.current_game_index.load(Ordering::SeqCst)) { game } else {
+                        let current_game = if let Some(game) = games.get_mut(state.selection.get().game) { game } else {
                             eprintln!("Current game index out of bounds");
                             return 0;
                         };
                         
-                        let weapon = if let Some(weapon) = current_game.weapons
-                            .as_mut()
-                            .map(|w| w.get_mut(&current_weapon_id))
-                            .flatten()
-                        {
-                            weapon 
-                        } else {
-                            eprintln!("Weapon not found: {}", current_weapon_id);
+                        let Some(weapons) = current_game.weapons.as_mut() else {
+                            drop(games);
+                            report_engine_error(state, format!("Weapon not found: {}", current_weapon_id));
                             return 0;
                         };
-
-                        let (dx_mut_ref, dy_mut_ref) = match weapon {
-                            Weapon::SingleFire(config) => (&mut config.dx, &mut config.dy),
-                            Weapon::FullAutoStandard(config) => (&mut config.dx, &mut config.dy),
-                            Weapon::SingleShot(config) => (&mut config.dx, &mut config.dy),
+                        let mut weapon = match resolved_weapon(weapons, &current_weapon_id) {
+                            Ok(weapon) => weapon,
+                            Err(e) => {
+                                drop(games);
+                                report_engine_error(state, e);
+                                return 0;
+                            }
                         };
+
+                        let (dx_mut_ref, dy_mut_ref) = weapon.offsets_mut();
                         *dx_mut_ref += if keyboard.VKey as i32 == VK_HOME { 0.1 } else if keyboard.VKey as i32 == VK_END { -0.1 } else { 0.0 };
                         *dy_mut_ref += if keyboard.VKey as i32 == VK_PRIOR { 0.1 } else if keyboard.VKey as i32 == VK_NEXT { -0.1 } else { 0.0 };
 
                         // Round the values to 2 decimal places
                         *dx_mut_ref = (*dx_mut_ref * 100.0).round() / 100.0;
                         *dy_mut_ref = (*dy_mut_ref * 100.0).round() / 100.0;
+                        let (dx, dy) = (*dx_mut_ref, *dy_mut_ref);
+
+                        // Variants keep the adjusted values as overrides, leaving their base as is
+                        match weapons.get_mut(&current_weapon_id) {
+                            Some(WeaponEntry::Variant(variant)) => {
+                                variant.overrides.insert("dx".to_string(), serde_json::json!(dx));
+                                variant.overrides.insert("dy".to_string(), serde_json::json!(dy));
+                            },
+                            Some(entry) => *entry = WeaponEntry::Weapon(weapon),
+                            None => {},
+                        }
 
                         // Emit an event that the config has been updated
                         drop(games); // Drop the lock before saving
-                        if let Err(e) = state.events_channel_sender.send(AppEvent::UpdatedGames {
+                        state.events.publish(AppEvent::UpdatedGames {
                             games: state.games.read_arc().clone(),
-                        }) {
-                            eprintln!("Failed to send event: {}", e);
-                        }
+                        });
 
                         // Save the updated config
                         if let Err(e) = save_data(state) {
@@ -375,44 +422,36 @@ fn to_wstring(s: &str) -> Vec<u16> {
 }
 
 fn handle_arrow_key_navigation(state: &AppState, key: i32) {
-    let current_game_index = state.current_game_index.load(Ordering::SeqCst);
-    let current_category_index = state.current_category_index.load(Ordering::SeqCst);
-    let current_loadout_index = state.current_loadout_index.load(Ordering::SeqCst);
-
-    let games = state.games.read_arc();
-    if let Some(game) = games.get(current_game_index) {
-        if let Some(category) = game.categories
-            .as_ref()
-            .and_then(|cats| cats.get(current_category_index))
-        {
-            let grid_layout = state.grid_layout_info.read_arc();
-            let total_loadouts = category.loadouts.len();
-            let per_row = grid_layout.loadouts_per_row;
-            
-            if let Some(new_index) = calculate_new_index(current_loadout_index, total_loadouts, per_row, key) {
-                drop(games);
-                drop(grid_layout);
-                
-                // Update the loadout selection
-                state.current_loadout_index.store(new_index, Ordering::SeqCst);
-                println!("Changed loadout to index {}", new_index);
-                
-                // Emit event for loadout change
-                if let Err(e) = state.events_channel_sender.send(AppEvent::SwitchedLoadout {
-                    loadout_ind: new_index,
-                }) {
-                    eprintln!("Failed to send event: {}", e);
-                }
-                
-                // Save the updated data
-                if let Err(e) = save_data(state) {
-                    eprintln!("Failed to save data: {}", e);
-                }
+    let per_row = state.grid_layout_info.read_arc().loadouts_per_row;
+    let previous_loadout = state.selection.get().loadout;
+
+    // Emits `SwitchedLoadout` for the frontend
+    let result = update_selection(state, |selection, games| {
+        let total_loadouts = games.get(selection.game)
+            .and_then(|game| game.categories.as_ref())
+            .and_then(|cats| cats.get(selection.category))
+            .map(|category| category.loadouts.len())
+            .unwrap_or(0);
+
+        Ok(match calculate_new_index(selection.loadout, total_loadouts, per_row, key) {
+            Some(new_index) => Selection { loadout: new_index, ..selection },
+            None => selection,
+        })
+    });
+
+    match result {
+        Ok(selection) if selection.loadout != previous_loadout => {
+            println!("Changed loadout to index {}", selection.loadout);
+
+            // Save the updated data
+            if let Err(e) = save_data(state) {
+                eprintln!("Failed to save data: {}", e);
             }
-        }
+        },
+        Ok(_) => {},
+        Err(e) => eprintln!("Failed to change loadout: {}", e),
     }
 }
-
 fn calculate_new_index(current_index: usize, total_items: usize, items_per_row: usize, key: i32) -> Option<usize> {
     if total_items == 0 || items_per_row == 0 {
         return None;
@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::motion::{LiveSink, MotionOutput};
+use crate::recoil::{fire_shot, shot_motion};
+use crate::stance::StanceModifiers;
+use crate::types::{ActiveWeapon, AppEvent, AppState, Weapon};
+use crate::winapi::{press_key, release_key};
+use crate::report_engine_error;
+
+/// Messages from the input listener (and commands) to the recoil worker
+#[derive(Clone, Debug)]
+pub enum RecoilCommand {
+    /// The trigger was pulled, `left_hold_active` is already set
+    Press,
+    /// The trigger was released, `left_hold_active` is already cleared
+    Release,
+    /// Forget the trigger cap timing of a weapon, sent when switching away from it
+    ClearTiming { weapon_id: String },
+    /// Keep the trigger cap timing of a weapon whose ID changed
+    RenameTiming { weapon_id: String, new_weapon_id: String },
+    Shutdown,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum RecoilWorkerStatus {
+    Stopped,
+    Idle,
+    Firing,
+}
+impl RecoilWorkerStatus {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Idle,
+            2 => Self::Firing,
+            _ => Self::Stopped,
+        }
+    }
+}
+
+struct RunningWorker {
+    sender: mpsc::Sender<RecoilCommand>,
+    thread: JoinHandle<()>,
+}
+
+/// Owns the long-lived thread that turns trigger presses into mouse movement
+#[derive(Clone)]
+pub struct RecoilWorkerHandle {
+    running: Arc<Mutex<Option<RunningWorker>>>,
+    status: Arc<AtomicU8>,
+}
+impl RecoilWorkerHandle {
+    pub fn new() -> Self {
+        Self {
+            running: Arc::new(Mutex::new(None)),
+            status: Arc::new(AtomicU8::new(RecoilWorkerStatus::Stopped as u8)),
+        }
+    }
+    pub fn start(&self, state: &AppState) -> Result<(), String> {
+        let mut running = self.running.lock();
+        if running.is_some() {
+            return Err("Recoil worker is already running".to_string());
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let mut worker = RecoilWorker {
+            state: state.clone(),
+            receiver,
+            last_shot_times: HashMap::new(),
+            stopping: false,
+        };
+        let thread = thread::Builder::new()
+            .name("recoil-worker".to_string())
+            .spawn(move || worker.run())
+            .map_err(|e| format!("Failed to spawn recoil worker: {}", e))?;
+
+        *running = Some(RunningWorker { sender, thread });
+        println!("Started recoil worker");
+
+        Ok(())
+    }
+    /// Stop the worker, aborting any spray in progress, and wait for its thread to exit
+    pub fn stop(&self) -> Result<(), String> {
+        let Some(worker) = self.running.lock().take() else {
+            return Err("Recoil worker is not running".to_string());
+        };
+
+        // If the worker already exited, joining below still reports how
+        let _ = worker.sender.send(RecoilCommand::Shutdown);
+        worker.thread.join()
+            .map_err(|_| "Recoil worker panicked".to_string())?;
+        println!("Stopped recoil worker");
+
+        Ok(())
+    }
+    pub fn restart(&self, state: &AppState) -> Result<(), String> {
+        if self.running.lock().is_some() {
+            self.stop()?;
+        }
+
+        self.start(state)
+    }
+    /// Send a command to the worker, returns `false` if it isn't running
+    pub fn send(&self, command: RecoilCommand) -> bool {
+        match &*self.running.lock() {
+            Some(worker) => worker.sender.send(command).is_ok(),
+            None => false,
+        }
+    }
+    pub fn status(&self) -> RecoilWorkerStatus {
+        RecoilWorkerStatus::from_u8(self.status.load(Ordering::SeqCst))
+    }
+}
+impl Default for RecoilWorkerHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Forget the trigger cap timing of the current weapon, used when switching weapons
+///
+/// Must be called before the firing context is refreshed with the new weapon.
+pub fn clear_current_weapon_timing(state: &AppState) {
+    if let Ok(resolved) = &state.firing_context.load().weapon {
+        let weapon_id = resolved.active.weapon_id.clone();
+        state.recoil_worker.send(RecoilCommand::ClearTiming { weapon_id });
+    }
+}
+
+struct RecoilWorker {
+    state: AppState,
+    receiver: mpsc::Receiver<RecoilCommand>,
+    /// Last shot time per weapon ID for the SingleFire trigger cap
+    last_shot_times: HashMap<String, Instant>,
+    stopping: bool,
+}
+impl RecoilWorker {
+    fn run(&mut self) {
+        self.set_status(RecoilWorkerStatus::Idle);
+
+        while !self.stopping {
+            // All senders are gone once the handle is dropped
+            let Ok(command) = self.receiver.recv() else { break; };
+
+            match command {
+                RecoilCommand::Press => {
+                    self.set_status(RecoilWorkerStatus::Firing);
+                    self.fire();
+                    if !self.stopping {
+                        self.set_status(RecoilWorkerStatus::Idle);
+                    }
+                },
+                command => self.handle_command(command),
+            }
+        }
+
+        self.set_status(RecoilWorkerStatus::Stopped);
+    }
+    fn set_status(&self, status: RecoilWorkerStatus) {
+        let previous = self.state.recoil_worker.status.swap(status as u8, Ordering::SeqCst);
+        if previous != status as u8 {
+            self.state.events.publish(AppEvent::RecoilWorkerStatusChanged { status });
+        }
+    }
+    fn handle_command(&mut self, command: RecoilCommand) {
+        match command {
+            // Presses while firing are part of the current spray, releases are seen through `left_hold_active`
+            RecoilCommand::Press | RecoilCommand::Release => {},
+            RecoilCommand::ClearTiming { weapon_id } => self.clear_shot_timing(&weapon_id),
+            RecoilCommand::RenameTiming { weapon_id, new_weapon_id } => self.rename_shot_timing(&weapon_id, new_weapon_id),
+            RecoilCommand::Shutdown => self.stopping = true,
+        }
+    }
+    /// Handle any commands that arrived mid-spray without blocking
+    fn process_pending(&mut self) {
+        while let Ok(command) = self.receiver.try_recv() {
+            self.handle_command(command);
+        }
+    }
+    /// Sleep for `duration`, handling commands as they arrive
+    fn wait(&mut self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            match self.receiver.recv_timeout(remaining) {
+                Ok(command) => self.handle_command(command),
+                Err(_) => break,
+            }
+            // Stopping or restarting the worker shouldn't wait for the rest of a delay
+            if self.stopping {
+                break;
+            }
+        }
+    }
+    /// Wait out a delay mid-spray, returning early on shutdown
+    fn wait_in_spray(&mut self, output: &mut MotionOutput<LiveSink>, duration: Duration) {
+        self.wait(duration);
+        output.reset_timeline();
+    }
+    fn trigger_held(&self) -> bool {
+        !self.stopping && self.state.left_hold_active.load(Ordering::SeqCst)
+    }
+    fn right_hold_satisfied(&self, require_right_hold: bool) -> bool {
+        !require_right_hold || self.state.right_hold_active.load(Ordering::SeqCst)
+    }
+    /// The weapon's scale in the current stance, read per shot as it can change mid-spray
+    fn stance_scale(&self, modifiers: &StanceModifiers, scale: (f32, f32)) -> (f32, f32) {
+        modifiers.apply(self.state.stance.get(), scale)
+    }
+    fn fire(&mut self) {
+        // The trigger may have been released again before this press was handled
+        if !self.trigger_held() {
+            return;
+        }
+
+        let mut shooting_started = false;
+        let mut shot_index = 0u32;
+        let mut output = MotionOutput::new(
+            LiveSink::start(),
+            self.state.firing_context.load().config.mouse_config.tick_rate,
+        );
+        let mut last_weapon_id = None;
+
+        'outer: loop {
+            // Don't touch the mouse at all while the engine is paused
+            if self.state.paused.load(Ordering::SeqCst) || self.stopping {
+                break 'outer;
+            }
+
+            // Take the resolved weapon and settings, never touching the games or config locks
+            let context = self.state.firing_context.load_full();
+            let effective_config = &context.config;
+
+            // Check that the right button is also held down
+            if !self.right_hold_satisfied(effective_config.keybinds.require_right_hold) {
+                // Only send StoppedShooting if we previously started shooting
+                if shooting_started {
+                    self.publish_spray_event(AppEvent::StoppedShooting);
+                    shooting_started = false;
+                    shot_index = 0;
+                }
+
+                if !self.trigger_held() {
+                    // If the left button is not held, exit the loop
+                    return;
+                }
+
+                self.wait(Duration::from_millis(10));
+                output.reset_timeline();
+                continue 'outer;
+            }
+
+            let resolved = match &context.weapon {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    report_engine_error(&self.state, e.clone());
+                    break 'outer;
+                }
+            };
+            let active_weapon = &resolved.active;
+            let weapon_id = active_weapon.weapon_id.clone();
+            let weapon_ind = resolved.weapon_ind;
+            let weapon = &resolved.weapon;
+
+            // Emit an event that shooting has started
+            if !shooting_started {
+                self.publish_spray_event(AppEvent::StartedShooting {
+                    weapon_ind,
+                    game: active_weapon.game.clone(),
+                    category: active_weapon.category.clone(),
+                    category_id: active_weapon.category_id.clone(),
+                    loadout: active_weapon.loadout.clone(),
+                    loadout_id: active_weapon.loadout_id.clone(),
+                    weapon_id: weapon_id.clone(),
+                });
+                shooting_started = true;
+            }
+
+            println!("Controlling weapon: {}", weapon_id);
+            last_weapon_id = Some(weapon_id.clone());
+            let scale = resolved.scale;
+            let require_right_hold = effective_config.keybinds.require_right_hold;
+            let stance_modifiers = &effective_config.stance_modifiers;
+            match weapon {
+                Weapon::FullAutoStandard(config) => {
+                    if !config.enabled {
+                        println!("FullAutoStandard weapon disabled: {}", weapon_id);
+                        break 'outer;
+                    }
+
+                    // The first shot always fires, later ones only while the trigger is held
+                    let mut iteration = 0;
+                    loop {
+                        fire_shot(&mut output, self.stance_scale(stance_modifiers, scale), shot_motion(weapon, iteration));
+                        self.record_shot(&weapon_id, &mut shot_index);
+                        self.process_pending();
+
+                        println!(":3 -");
+                        iteration += 1;
+
+                        if !(self.trigger_held() && self.right_hold_satisfied(require_right_hold)) {
+                            break;
+                        }
+
+                        // Check if the weapon has been changed
+                        if self.weapon_changed(active_weapon) {
+                            // If the weapon has changed, exit the loop
+                            println!("Weapon changed while firing, exiting hold loop.");
+                            continue 'outer;
+                        }
+                    }
+                }
+                Weapon::SingleFire(config) => {
+                    let trigger_delay = Duration::from_millis(config.trigger_delay_ms as u64);
+                    let release_delay: Duration = Duration::from_millis(config.release_delay_ms as u64);
+
+                    while self.trigger_held() && self.right_hold_satisfied(require_right_hold) {
+                        // Check if weapon is ready to fire (respecting trigger cap)
+                        if !self.can_fire_single_fire_weapon(&weapon_id, config.trigger_delay_ms, config.recoil_completion_ms) {
+                            // Weapon is still in trigger cap period, wait a bit and check again
+                            self.wait(Duration::from_millis(10));
+                            output.reset_timeline();
+                            continue;
+                        }
+
+                        // Weapon is ready to fire
+                        press_key(effective_config.keybinds.alternative_fire);
+
+                        // Record that we fired a shot (for trigger cap tracking)
+                        self.record_shot_fired(&weapon_id);
+                        self.record_shot(&weapon_id, &mut shot_index);
+
+                        // Only apply recoil control if enabled
+                        let motion = shot_motion(weapon, 0);
+                        if config.enabled {
+                            fire_shot(&mut output, self.stance_scale(stance_modifiers, scale), motion);
+                        } else {
+                            // If recoil control is disabled, just wait for the recoil completion time
+                            self.wait_in_spray(&mut output, motion.duration);
+                        }
+                        self.process_pending();
+
+                        if !self.trigger_held() ||
+                            !config.autofire ||
+                            !self.right_hold_satisfied(require_right_hold)
+                        {
+                            break 'outer;
+                        }
+
+                        self.wait_in_spray(&mut output, release_delay);
+
+                        release_key(effective_config.keybinds.alternative_fire);
+
+                        self.wait_in_spray(&mut output, trigger_delay);
+
+                        // Check if the weapon has been changed
+                        if self.weapon_changed(active_weapon) {
+                            // If the weapon has changed, clear timing and exit the loop
+                            self.clear_shot_timing(&weapon_id);
+                            println!("Weapon changed while firing, exiting hold loop.");
+                            continue 'outer;
+                        }
+                        println!("[SF] :3 -");
+                    }
+                },
+                Weapon::SingleShot(config) => {
+                    if !config.enabled {
+                        println!("SingleShot weapon disabled: {}", weapon_id);
+                        break 'outer;
+                    }
+
+                    self.record_shot(&weapon_id, &mut shot_index);
+
+                    // Move down for the shot
+                    fire_shot(&mut output, self.stance_scale(stance_modifiers, scale), shot_motion(weapon, 0));
+
+                    break 'outer;
+                }
+            }
+            if !self.trigger_held() {
+                println!("Left button released, exiting hold loop.");
+                break;
+            }
+        }
+
+        // Emit an event that shooting has stopped (only if it was started)
+        if shooting_started {
+            self.publish_spray_event(AppEvent::StoppedShooting);
+        }
+
+        // Report how closely the spray kept to its schedule
+        let jitter = output.jitter();
+        if let Some(weapon_id) = last_weapon_id.filter(|_| jitter.ticks > 0) {
+            println!("Spray timing: {} ticks, {:.0}us mean / {:.0}us max late", jitter.ticks, jitter.mean_us, jitter.max_us);
+            self.state.events.publish(AppEvent::SprayTiming { weapon_id, jitter });
+        }
+    }
+
+    /// Whether the selected weapon is no longer `active`, e.g. after switching slot,
+    /// loadout or category, or choosing another weapon in the same slot
+    fn weapon_changed(&self, active: &ActiveWeapon) -> bool {
+        !matches!(&self.state.firing_context.load().weapon, Ok(resolved) if
+            resolved.active.game == active.game &&
+            resolved.active.loadout_id == active.loadout_id &&
+            resolved.active.weapon_id == active.weapon_id
+        )
+    }
+
+    /// Count a spray event in the stats and publish it
+    ///
+    /// Stats are fed here rather than through a subscriber, which would miss events when lagging behind a long spray.
+    fn publish_spray_event(&self, event: AppEvent) {
+        self.state.stats_recorder.record(&event);
+        self.state.events.publish(event);
+    }
+
+    /// Emit a `ShotFired` event and advance the spray's shot counter
+    fn record_shot(&self, weapon_id: &str, shot_index: &mut u32) {
+        self.publish_spray_event(AppEvent::ShotFired {
+            weapon_id: weapon_id.to_string(),
+            shot_index: *shot_index,
+        });
+        *shot_index += 1;
+    }
+
+    /// Check if enough time has passed since the last shot for SingleFire weapons
+    /// Returns true if the weapon is ready to fire, false if still in trigger cap period
+    fn can_fire_single_fire_weapon(
+        &self,
+        weapon_id: &str,
+        trigger_delay_ms: u32,
+        recoil_completion_ms: u32,
+    ) -> bool {
+        let trigger_cap_duration = Duration::from_millis((trigger_delay_ms + recoil_completion_ms) as u64);
+        let now = Instant::now();
+
+        if let Some(last_shot_time) = self.last_shot_times.get(weapon_id) {
+            let time_since_last_shot = now.duration_since(*last_shot_time);
+            if time_since_last_shot < trigger_cap_duration {
+                let remaining_ms = (trigger_cap_duration - time_since_last_shot).as_millis();
+                println!("SingleFire weapon '{}' still in trigger cap, {}ms remaining", weapon_id, remaining_ms);
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Record that a shot was fired for trigger cap tracking
+    fn record_shot_fired(&mut self, weapon_id: &str) {
+        self.last_shot_times.insert(weapon_id.to_string(), Instant::now());
+        println!("Recorded shot fired for weapon '{}'", weapon_id);
+    }
+
+    /// Clear shot timing for a weapon (called when switching weapons)
+    fn clear_shot_timing(&mut self, weapon_id: &str) {
+        self.last_shot_times.remove(weapon_id);
+        println!("Cleared shot timing for weapon '{}'", weapon_id);
+    }
+
+    /// Move shot timing to a weapon's new ID (called when a weapon is renamed)
+    fn rename_shot_timing(&mut self, weapon_id: &str, new_weapon_id: String) {
+        if let Some(last_shot_time) = self.last_shot_times.remove(weapon_id) {
+            self.last_shot_times.insert(new_weapon_id, last_shot_time);
+        }
+    }
+}
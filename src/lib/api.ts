@@ -5,10 +5,29 @@ import {
     current_loadout_index,
     current_weapon_index,
     shooting,
+    paused,
+    recoil_worker_status,
+    stance,
+    last_spray_timing,
+    resolved_weapons,
     current_category_index,
     current_game_index,
     config,
     type Game,
+    type JitterStats,
+    type KeyStatus,
+    type RecoilWorkerStatus,
+    type SensitivityFormula,
+    type SensitivityProfile,
+    type SensitivityResult,
+    type Stance,
+    type StatsScope,
+    type Attachment,
+    type IntegrityReport,
+    type StatsSummary,
+    type Weapon,
+    type WeaponEntry,
+    type WeaponUsage,
     errors,
     version
 } from '../stores/state';
@@ -17,6 +36,10 @@ type UpdatedGamesEvent = {
     event: 'UpdatedGames';
     data: { games: Game[]; };
 };
+type SwitchedGameEvent = {
+    event: 'SwitchedGame';
+    data: { game_ind: number };
+};
 type SwitchedWeaponEvent = {
     event: 'SwitchedWeapon';
     data: { weapon_ind: number };
@@ -31,12 +54,53 @@ type SwitchedCategoryEvent = {
 };
 type StartedShootingEvent = {
     event: 'StartedShooting';
-    data: { weapon_ind: number };
+    data: { weapon_ind: number; game: string; category: string; category_id: string; loadout: string; loadout_id: string; weapon_id: string };
+};
+type ShotFiredEvent = {
+    event: 'ShotFired';
+    data: { weapon_id: string; shot_index: number };
 };
 type StoppedShootingEvent = {
     event: 'StoppedShooting';
 };
-type Event = UpdatedGamesEvent | SwitchedWeaponEvent | SwitchedLoadoutEvent | SwitchedCategoryEvent | StartedShootingEvent | StoppedShootingEvent;
+type SavedEvent = {
+    event: 'Saved';
+};
+type LicenseStatusChangedEvent = {
+    event: 'LicenseStatusChanged';
+    data: { game: string; key_status: KeyStatus | null };
+};
+type EngineErrorEvent = {
+    event: 'EngineError';
+    data: { message: string };
+};
+type PausedChangedEvent = {
+    event: 'PausedChanged';
+    data: { paused: boolean };
+};
+type RecoilWorkerStatusChangedEvent = {
+    event: 'RecoilWorkerStatusChanged';
+    data: { status: RecoilWorkerStatus };
+};
+type SprayTimingEvent = {
+    event: 'SprayTiming';
+    data: { weapon_id: string; jitter: JitterStats };
+};
+type GameDataIssuesEvent = {
+    event: 'GameDataIssues';
+    data: { reports: IntegrityReport[] };
+};
+type StanceChangedEvent = {
+    event: 'StanceChanged';
+    data: { stance: Stance };
+};
+type KnownEvent = UpdatedGamesEvent | SwitchedGameEvent | SwitchedWeaponEvent | SwitchedLoadoutEvent | SwitchedCategoryEvent
+    | StartedShootingEvent | ShotFiredEvent | StoppedShootingEvent | SavedEvent | LicenseStatusChangedEvent | EngineErrorEvent | PausedChangedEvent
+    | RecoilWorkerStatusChangedEvent | SprayTimingEvent | GameDataIssuesEvent | StanceChangedEvent;
+
+// Newest event schema this frontend understands, see `EVENT_SCHEMA_VERSION` in the backend
+const SUPPORTED_EVENT_SCHEMA_VERSION = 3;
+type Event = KnownEvent & { schema_version: number };
 
 let channel: Channel<Event>;
 
@@ -57,8 +121,14 @@ export async function initialize() {
         config.set(loadedConfig as any);
         console.log('Config loaded:', loadedConfig);
 
+        recoil_worker_status.set(await invoke('get_recoil_worker_status') as RecoilWorkerStatus);
+        stance.set(await invoke('get_stance') as Stance);
+
         channel = new Channel<Event>();
         channel.onmessage = handleChannelEvent;
+        window.addEventListener('beforeunload', () => {
+            invoke('stop_channel_reads').catch(() => {});
+        });
         await invoke('start_channel_reads', { channel });
     } catch (error: any) {
         handleError('Initialization failed', error);
@@ -73,12 +143,19 @@ function handleError(context: string, error: any) {
 }
 function handleChannelEvent(message: Event) {
     console.log('Received channel event:', message); // Debug log to see what we're receiving
+
+    if (message.schema_version > SUPPORTED_EVENT_SCHEMA_VERSION) {
+        console.warn(`Event schema v${message.schema_version} is newer than supported v${SUPPORTED_EVENT_SCHEMA_VERSION}`);
+    }
     
     switch (message.event) {
         case 'UpdatedGames':
             games.set(message.data.games as any);
             console.log('Games updated:', message.data.games);
             break;
+        case 'SwitchedGame':
+            current_game_index.set(message.data.game_ind);
+            break;
         case 'SwitchedWeapon':
             current_weapon_index.set(message.data.weapon_ind);
             console.log('Weapon switched to index:', message.data.weapon_ind);
@@ -95,13 +172,47 @@ function handleChannelEvent(message: Event) {
             shooting.set(true);
             current_weapon_index.set(message.data.weapon_ind);
             break;
+        case 'ShotFired':
+            break;
         case 'StoppedShooting':
             shooting.set(false);
             break;
+        case 'Saved':
+            break;
+        case 'LicenseStatusChanged':
+            games.update((currentGames) => currentGames.map((game) =>
+                game.name === message.data.game
+                    ? { ...game, key_status: message.data.key_status ?? undefined }
+                    : game
+            ));
+            break;
+        case 'EngineError':
+            errors.update((currentErrors) => [...currentErrors, message.data.message]);
+            break;
+        case 'PausedChanged':
+            paused.set(message.data.paused);
+            break;
+        case 'RecoilWorkerStatusChanged':
+            recoil_worker_status.set(message.data.status);
+            break;
+        case 'SprayTiming':
+            last_spray_timing.set(message.data);
+            break;
+        case 'GameDataIssues':
+            errors.update((currentErrors) => [
+                ...currentErrors,
+                ...message.data.reports.map((report) => {
+                    const count = report.issues.filter((issue) => issue.severity === 'Error').length;
+                    return `${report.game} has ${count} data error(s), check the game data to repair them`;
+                })
+            ]);
+            break;
+        case 'StanceChanged':
+            stance.set(message.data.stance);
+            break;
         default:
-            console.warn('Unknown channel message:', message);
-            console.warn('Event type received:', typeof (message as any).event, (message as any).event);
-            console.warn('Full message structure:', JSON.stringify(message, null, 2));
+            // Event kinds newer than this frontend are safe to ignore
+            console.debug('Ignoring unknown channel event:', (message as any).event);
     }
 }
 export function changeGame(index: number) {
@@ -109,25 +220,192 @@ export function changeGame(index: number) {
         .then((newIndex) => current_game_index.set(newIndex as number))
         .catch((error) => handleError('Change to game failed', error));
 }
-export function changeCategory(index: number) {
-    invoke('change_category', { newCategoryIndex: index })
+export function changeCategory(categoryId: string) {
+    invoke('change_category', { categoryId })
         .then((newIndex) => current_category_index.set(newIndex as number))
         .catch((error) => handleError('Change to category failed', error));
 }
-export function changeLoadout(index: number) {
-    invoke('change_loadout', { newLoadoutIndex: index })
+export function changeLoadout(loadoutId: string) {
+    invoke('change_loadout', { loadoutId })
         .then((newIndex) => current_loadout_index.set(newIndex as number))
         .catch((error) => handleError('Change to loadout failed', error));
 }
-export function changePrimaryWeapon(index: number) {
-    invoke('change_primary_weapon', { newPrimaryIndex: index })
+export function changeSlotWeapon(slotIndex: number, index: number) {
+    invoke('change_slot_weapon', { slotIndex, newWeaponIndex: index })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Change weapon failed', error));
+}
+export function createCategory(name: string) {
+    invoke('create_category', { name })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Create category failed', error));
+}
+export function renameCategory(categoryId: string, name: string) {
+    invoke('rename_category', { categoryId, name })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Rename category failed', error));
+}
+export function deleteCategory(categoryId: string) {
+    invoke('delete_category', { categoryId })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Delete category failed', error));
+}
+export function duplicateCategory(categoryId: string, name: string) {
+    invoke('duplicate_category', { categoryId, name })
         .then((new_games) => games.set(new_games as Game[]))
-        .catch((error) => handleError('Change primary weapon failed', error));
+        .catch((error) => handleError('Duplicate category failed', error));
 }
-export function changeSecondaryWeapon(index: number) {
-    invoke('change_secondary_weapon', { newSecondaryIndex: index })
+export function moveCategory(categoryId: string, newIndex: number) {
+    invoke('move_category', { categoryId, newIndex })
         .then((new_games) => games.set(new_games as Game[]))
-        .catch((error) => handleError('Change secondary weapon failed', error));
+        .catch((error) => handleError('Move category failed', error));
+}
+export function createLoadout(categoryId: string, name: string) {
+    invoke('create_loadout', { categoryId, name })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Create loadout failed', error));
+}
+export function renameLoadout(loadoutId: string, name: string) {
+    invoke('rename_loadout', { loadoutId, name })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Rename loadout failed', error));
+}
+export function deleteLoadout(loadoutId: string) {
+    invoke('delete_loadout', { loadoutId })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Delete loadout failed', error));
+}
+export function duplicateLoadout(loadoutId: string, name: string) {
+    invoke('duplicate_loadout', { loadoutId, name })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Duplicate loadout failed', error));
+}
+export function moveLoadout(loadoutId: string, categoryId: string, newIndex: number) {
+    invoke('move_loadout', { loadoutId, categoryId, newIndex })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Move loadout failed', error));
+}
+export function addWeaponSlot(loadoutId: string, name: string) {
+    invoke('add_weapon_slot', { loadoutId, name })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Add weapon slot failed', error));
+}
+export function removeWeaponSlot(loadoutId: string, slotIndex: number) {
+    invoke('remove_weapon_slot', { loadoutId, slotIndex })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Remove weapon slot failed', error));
+}
+export function addLoadoutWeapon(loadoutId: string, slotIndex: number, weaponId: string) {
+    invoke('add_loadout_weapon', { loadoutId, slotIndex, weaponId })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Add weapon to loadout failed', error));
+}
+export function removeLoadoutWeapon(loadoutId: string, slotIndex: number, weaponId: string) {
+    invoke('remove_loadout_weapon', { loadoutId, slotIndex, weaponId })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Remove weapon from loadout failed', error));
+}
+export function createWeapon(weaponId: string, kind: 'SingleFire' | 'SingleShot' | 'FullAutoStandard', name: string) {
+    invoke('create_weapon', { weaponId, kind, name })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Create weapon failed', error));
+}
+export function duplicateWeapon(weaponId: string, newWeaponId: string, name: string) {
+    invoke('duplicate_weapon', { weaponId, newWeaponId, name })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Duplicate weapon failed', error));
+}
+export function deleteWeapon(weaponId: string, removeReferences: boolean = false) {
+    invoke('delete_weapon', { weaponId, removeReferences })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Delete weapon failed', error));
+}
+export function renameWeaponId(weaponId: string, newWeaponId: string) {
+    invoke('rename_weapon_id', { weaponId, newWeaponId })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Rename weapon failed', error));
+}
+export async function getWeaponUsages(): Promise<Record<string, WeaponUsage[]> | null> {
+    try {
+        return await invoke('get_weapon_usages') as Record<string, WeaponUsage[]>;
+    } catch (error) {
+        handleError('Get weapon usages failed', error);
+        return null;
+    }
+}
+export async function checkGameData(repair: boolean = false): Promise<IntegrityReport[] | null> {
+    try {
+        return await invoke('check_game_data', { repair }) as IntegrityReport[];
+    } catch (error) {
+        handleError('Check game data failed', error);
+        return null;
+    }
+}
+export function createWeaponVariant(weaponId: string, baseWeaponId: string, name: string) {
+    invoke('create_weapon_variant', { weaponId, baseWeaponId, name })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Create weapon variant failed', error));
+}
+export async function getRawWeapons(gameName: string): Promise<Record<string, WeaponEntry> | null> {
+    try {
+        return await invoke('get_raw_weapons', { gameName }) as Record<string, WeaponEntry>;
+    } catch (error) {
+        handleError('Get weapons failed', error);
+        return null;
+    }
+}
+export async function refreshResolvedWeapons(gameName: string) {
+    try {
+        resolved_weapons.set(await invoke('get_resolved_weapons', { gameName }) as Record<string, Weapon>);
+    } catch (error) {
+        handleError('Get resolved weapons failed', error);
+    }
+}
+export function setSlotAttachments(loadoutId: string, slotIndex: number, weaponId: string, attachmentIds: string[]) {
+    invoke('set_slot_attachments', { loadoutId, slotIndex, weaponId, attachmentIds })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Set attachments failed', error));
+}
+export function saveAttachment(attachmentId: string, attachment: Attachment) {
+    invoke('save_attachment', { attachmentId, attachment })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Save attachment failed', error));
+}
+export function deleteAttachment(attachmentId: string, removeReferences: boolean = false) {
+    invoke('delete_attachment', { attachmentId, removeReferences })
+        .then((new_games) => games.set(new_games as Game[]))
+        .catch((error) => handleError('Delete attachment failed', error));
+}
+export function setPaused(newPaused: boolean) {
+    invoke('set_paused', { paused: newPaused })
+        .then((result) => paused.set(result as boolean))
+        .catch((error) => handleError('Pause engine failed', error));
+}
+export function setStance(newStance: Stance) {
+    invoke('set_stance', { stance: newStance })
+        .then((result) => stance.set(result as Stance))
+        .catch((error) => handleError('Set stance failed', error));
+}
+export async function refreshRecoilWorkerStatus() {
+    try {
+        recoil_worker_status.set(await invoke('get_recoil_worker_status') as RecoilWorkerStatus);
+    } catch (error) {
+        handleError('Get recoil worker status failed', error);
+    }
+}
+export async function restartRecoilWorker() {
+    try {
+        recoil_worker_status.set(await invoke('restart_recoil_worker') as RecoilWorkerStatus);
+    } catch (error) {
+        handleError('Restart recoil worker failed', error);
+    }
+}
+export async function stopRecoilWorker() {
+    try {
+        recoil_worker_status.set(await invoke('stop_recoil_worker') as RecoilWorkerStatus);
+    } catch (error) {
+        handleError('Stop recoil worker failed', error);
+    }
 }
 export function changeHorizontalMultiplier(newMultiplier: number) {
     invoke('change_horizontal_multiplier', { newMultiplier })
@@ -154,11 +432,66 @@ export function changeScrollWheelWeaponSwap(enabled: boolean) {
         .then((new_config) => config.set(new_config as any))
         .catch((error) => handleError('Change to scroll wheel weapon swap failed', error));
 }
+export function changeMouseDpi(newDpi: number | null) {
+    invoke('change_mouse_dpi', { newDpi })
+        .then((new_config) => config.set(new_config as any))
+        .catch((error) => handleError('Change to mouse DPI failed', error));
+}
+export function changeTickRate(newTickRate: number) {
+    invoke('change_tick_rate', { newTickRate })
+        .then((new_config) => config.set(new_config as any))
+        .catch((error) => handleError('Change to tick rate failed', error));
+}
+export function changeLocalApiSettings(enabled: boolean, port: number) {
+    invoke('change_local_api_settings', { enabled, port })
+        .then((new_config) => config.set(new_config as any))
+        .catch((error) => handleError('Change to local API settings failed', error));
+}
+export function regenerateLocalApiToken() {
+    invoke('regenerate_local_api_token')
+        .then((new_config) => config.set(new_config as any))
+        .catch((error) => handleError('Regenerate local API token failed', error));
+}
 export function changeSetting(setting: string, value: string | boolean | number) {
     invoke('change_setting', { setting, value })
         .then((new_config) => config.set(new_config as any))
         .catch((error) => handleError('Change to settings failed', error));
 }
+export function changeGameSetting(gameName: string, setting: string, value: string | boolean | number | null) {
+    invoke('change_game_setting', { gameName, setting, value })
+        .then((new_config) => config.set(new_config as any))
+        .catch((error) => handleError('Change to game settings failed', error));
+}
+export function changeStanceModifier(gameName: string, stance: Stance, horizontalMultiplier: number, verticalMultiplier: number) {
+    invoke('change_stance_modifier', { gameName, stance, horizontalMultiplier, verticalMultiplier })
+        .then((new_config) => config.set(new_config as any))
+        .catch((error) => handleError('Change to stance multipliers failed', error));
+}
+export async function calculateSensitivity(
+    reference: SensitivityProfile,
+    user: SensitivityProfile,
+    opticZoom: number,
+    options: { gameName?: string; formula?: SensitivityFormula; apply?: boolean } = {}
+): Promise<SensitivityResult | null> {
+    try {
+        const result = await invoke('calculate_sensitivity', {
+            gameName: options.gameName ?? null,
+            reference,
+            user,
+            opticZoom,
+            formula: options.formula ?? null,
+            apply: options.apply ?? false
+        }) as SensitivityResult;
+
+        if (options.apply) {
+            config.set(await invoke('get_config') as any);
+        }
+        return result;
+    } catch (error) {
+        handleError('Calculate sensitivity failed', error);
+        return null;
+    }
+}
 export async function setWeaponConfig ( weaponId: string, field: string, newValue: any ) {
     invoke('change_weapon_config', { weaponId, field, newValue })
         .then((new_games) => games.set(new_games as any))
@@ -176,6 +509,30 @@ export async function submitGameKey(gameName: string, key: string): Promise<Game
     }
 }
 
+export async function getStatsSummary(gameName: string, scope: StatsScope): Promise<StatsSummary | null> {
+    try {
+        return await invoke('get_stats_summary', { gameName, scope }) as StatsSummary;
+    } catch (error) {
+        handleError('Get stats summary failed', error);
+        return null;
+    }
+}
+export async function getDailyStats(gameName: string): Promise<Record<string, StatsSummary> | null> {
+    try {
+        return await invoke('get_daily_stats', { gameName }) as Record<string, StatsSummary>;
+    } catch (error) {
+        handleError('Get daily stats failed', error);
+        return null;
+    }
+}
+export async function resetStats(gameName: string | null = null) {
+    try {
+        await invoke('reset_stats', { gameName });
+    } catch (error) {
+        handleError('Reset stats failed', error);
+    }
+}
+
 export function clearErrors() {
     errors.set([]);
 }
@@ -7,16 +7,22 @@ export interface Game {
 }
 
 export interface Category {
+    id: string;
     name: string;
     loadouts: Loadout[];
 }
 
 export interface Loadout {
+    id: string;
+    name: string;
+    slots: WeaponSlot[];
+}
+
+export interface WeaponSlot {
     name: string;
-    primaries: string[];
-    secondaries: string[];
-    selected_primary: number;
-    selected_secondary: number;
+    weapons: string[];
+    selected: number;
+    attachments?: Record<string, string[]>;
 }
 
 export interface Weapon {
@@ -29,7 +35,11 @@ export interface Config {
     keybinds: {
         primary_weapon: string;
         secondary_weapon: string;
+        extra_slot_keys: string[];
         alternative_fire: string;
+        crouch: string;
+        prone: string;
+        stance_key_mode: 'Hold' | 'Toggle';
     };
     mouse_config: {
         horizontal_multiplier: number;
@@ -46,7 +56,7 @@ export interface Config {
 export interface KeybindConfigOption {
     label: string;
     description: string;
-    type: 'keybind' | 'checkbox' | 'slider' | 'char';
+    type: 'keybind' | 'checkbox' | 'slider' | 'char' | 'text';
     key: string;
     value: any;
 }
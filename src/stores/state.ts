@@ -5,7 +5,13 @@ type KeybindConfig = {
     require_right_hold: boolean;
     primary_weapon: string;
     secondary_weapon: string;
+    /** Switch keys for the third weapon slot onwards */
+    extra_slot_keys: string[];
     alternative_fire: string;
+    crouch: string;
+    prone: string;
+    /** Whether the crouch and prone keys are held or toggle the stance */
+    stance_key_mode: StanceKeyMode;
 };
 type MouseConfig = {
     horizontal_multiplier: number;
@@ -13,6 +19,47 @@ type MouseConfig = {
     acog_horizontal_multiplier: number;
     acog_vertical_multiplier: number;
     scroll_wheel_weapon_swap: boolean;
+    dpi?: number | null;
+    sensitivity?: number | null;
+    tick_rate: number;
+};
+type KeybindOverrides = {
+    require_right_hold?: boolean | null;
+    primary_weapon?: string | null;
+    secondary_weapon?: string | null;
+    extra_slot_keys?: string[] | null;
+    alternative_fire?: string | null;
+    crouch?: string | null;
+    prone?: string | null;
+    stance_key_mode?: StanceKeyMode | null;
+};
+type MouseOverrides = {
+    horizontal_multiplier?: number | null;
+    vertical_multiplier?: number | null;
+    acog_horizontal_multiplier?: number | null;
+    acog_vertical_multiplier?: number | null;
+    sensitivity?: number | null;
+};
+export type Stance = 'Standing' | 'Crouching' | 'Prone';
+export type StanceKeyMode = 'Hold' | 'Toggle';
+export type StanceMultipliers = {
+    horizontal_multiplier: number;
+    vertical_multiplier: number;
+};
+// Standing is never scaled
+export type StanceModifiers = {
+    crouching: StanceMultipliers;
+    prone: StanceMultipliers;
+};
+export type GameSettings = {
+    keybinds: KeybindOverrides;
+    mouse_config: MouseOverrides;
+    stance_modifiers: StanceModifiers;
+};
+type LocalApiConfig = {
+    enabled: boolean;
+    port: number;
+    token: string;
 };
 type ThemeConfig = {
     accent_color: string;
@@ -20,6 +67,8 @@ type ThemeConfig = {
 export type GlobalConfig = {
     keybinds: KeybindConfig;
     mouse_config: MouseConfig;
+    game_settings: Record<string, GameSettings>;
+    local_api: LocalApiConfig;
     theme_config: ThemeConfig;
 };
 
@@ -29,6 +78,57 @@ export type Game = {
     key_status?: KeyStatus;
     categories?: Category[];
     weapons?: Weapons;
+    attachments?: Record<string, Attachment>;
+    sensitivity_formula?: SensitivityFormula | null;
+    reference_dpi?: number | null;
+    reference_sensitivity?: number | null;
+};
+
+export type UsageStats = {
+    sprays: number;
+    shots: number;
+    spray_ms: number;
+};
+export type StatsSummary = {
+    totals: UsageStats;
+    weapons: Record<string, UsageStats>;
+    loadouts: Record<string, UsageStats>;
+};
+export type StatsScope = 'Session' | 'Today' | 'AllTime';
+
+export type ValidationIssue = {
+    severity: 'Error' | 'Warning';
+    location: string;
+    message: string;
+};
+export type IntegrityReport = {
+    game: string;
+    // Issues left after any repairs
+    issues: ValidationIssue[];
+    repairs: { location: string; message: string }[];
+};
+// A loadout slot that holds a weapon
+export type WeaponUsage = {
+    category_id: string;
+    category: string;
+    loadout_id: string;
+    loadout: string;
+    slot: string;
+};
+
+export type SensitivityFormula = 'Linear' | 'FocalLength';
+export type SensitivityProfile = {
+    dpi: number;
+    sensitivity: number;
+    vertical_ratio?: number;
+    fov: number;
+    optic_sensitivity?: number;
+};
+export type SensitivityResult = {
+    horizontal_multiplier: number;
+    vertical_multiplier: number;
+    acog_horizontal_multiplier: number;
+    acog_vertical_multiplier: number;
 };
 
 export type KeyStatus = 
@@ -38,17 +138,31 @@ export type KeyStatus =
     | { type: 'Banned'; key: string }
     | { type: 'HWIDMismatch'; key: string };
 export type Category = {
+    id: string;
     name: string;
     loadouts: Loadout[];
 };
 export type Loadout = {
+    id: string;
     name: string;
     icon_url?: string;
     icon_only: boolean;
-    primaries: string[];
-    secondaries: string[];
-    selected_primary: number;
-    selected_secondary: number;
+    slots: WeaponSlot[];
+};
+export type WeaponSlot = {
+    name: string;
+    weapons: string[];
+    selected: number;
+    // Attachment IDs by weapon ID
+    attachments?: Record<string, string[]>;
+};
+export type Attachment = {
+    name: string;
+    description?: string | null;
+    horizontal_multiplier: number;
+    vertical_multiplier: number;
+    dx_offset: number;
+    dy_offset: number;
 };
 export type SingleFireConfig = {
     name: string;
@@ -85,7 +199,22 @@ export type Weapon = {
     config: SingleFireConfig | SingleShotConfig | FullAutoStandardConfig;
 };
 
-export type Weapons = Record<string, Weapon>;
+// A weapon that takes every field it doesn't override from its base weapon
+export type WeaponVariant = {
+    base: string;
+    overrides: Partial<SingleFireConfig & SingleShotConfig & FullAutoStandardConfig>;
+};
+export type WeaponEntry = Weapon | WeaponVariant;
+
+// As stored, see `resolved_weapons` for what the engine fires
+export type Weapons = Record<string, WeaponEntry>;
+
+export type RecoilWorkerStatus = 'Stopped' | 'Idle' | 'Firing';
+export type JitterStats = {
+    ticks: number;
+    mean_us: number;
+    max_us: number;
+};
 
 
 // App-wide reactive state
@@ -95,7 +224,11 @@ export const config = writable<GlobalConfig>({
         require_right_hold: true,
         primary_weapon: '1',
         secondary_weapon: '2',
+        extra_slot_keys: ['3', '4'],
         alternative_fire: 'm',
+        crouch: 'c',
+        prone: 'z',
+        stance_key_mode: 'Hold',
     },
     mouse_config: {
         horizontal_multiplier: 1.0,
@@ -103,6 +236,15 @@ export const config = writable<GlobalConfig>({
         acog_horizontal_multiplier: 2.5,
         acog_vertical_multiplier: 2.5,
         scroll_wheel_weapon_swap: true,
+        dpi: null,
+        sensitivity: null,
+        tick_rate: 500,
+    },
+    game_settings: {},
+    local_api: {
+        enabled: false,
+        port: 4778,
+        token: '',
     },
     theme_config: {
         accent_color: '#bf0f70', // Default accent color
@@ -113,5 +255,10 @@ export const current_category_index = writable(0);
 export const current_loadout_index = writable(0);
 export const current_weapon_index = writable(0);
 export const shooting = writable(false);
+export const paused = writable(false);
+export const recoil_worker_status = writable<RecoilWorkerStatus>('Stopped');
+export const stance = writable<Stance>('Standing');
+export const last_spray_timing = writable<{ weapon_id: string; jitter: JitterStats } | null>(null);
+export const resolved_weapons = writable<Record<string, Weapon>>({});
 export const errors = writable<string[]>([]);
 export const version = writable<string>('?.?.?');
\ No newline at end of file